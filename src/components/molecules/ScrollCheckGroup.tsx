@@ -73,6 +73,42 @@ const SHOT_BEHAVIOR_OPTIONS = {
     LABEL: '重力減衰/加速',
     VALUE: 6,
   },
+  HOMING: {
+    LABEL: '誘導',
+    VALUE: 7,
+  },
+  PATH: {
+    LABEL: 'パス',
+    VALUE: 8,
+  },
+  WAVE: {
+    LABEL: '揺れ',
+    VALUE: 9,
+  },
+  SPLIT: {
+    LABEL: '分裂',
+    VALUE: 10,
+  },
+  EMIT: {
+    LABEL: '発射台',
+    VALUE: 11,
+  },
+  CURVY_LASER: {
+    LABEL: '曲がるレーザー',
+    VALUE: 12,
+  },
+  ORBIT: {
+    LABEL: '回転',
+    VALUE: 13,
+  },
+  WRAP: {
+    LABEL: 'ループ',
+    VALUE: 14,
+  },
+  CURVE: {
+    LABEL: '曲がる',
+    VALUE: 15,
+  },
 };
 const shotBehaviorOptions = Object.entries(SHOT_BEHAVIOR_OPTIONS)
   .map(([key, schema]) => {
@@ -65,6 +65,44 @@ export const Canvas: React.FC<CanvasProps> = forwardRef<CanvasHandler>(({}, ref)
     initialize();
   }, [])
 
+  /** キー入力を自機の入力状態に反映 */
+  useEffect(() => {
+    const keys = new Set<string>();
+    const inputKeys = ['ArrowUp', 'ArrowDown', 'ArrowLeft', 'ArrowRight', 'Shift'];
+    // フォームの入力中はキー操作を奪わない
+    const isFormTarget = (target: EventTarget | null) =>
+      target instanceof HTMLInputElement || target instanceof HTMLSelectElement || target instanceof HTMLTextAreaElement;
+    const applyInput = () => {
+      glInstanceRef.current?.set_input(
+        keys.has('ArrowUp'),
+        keys.has('ArrowDown'),
+        keys.has('ArrowLeft'),
+        keys.has('ArrowRight'),
+        keys.has('Shift'),
+      );
+    }
+    const onKeyDown = (e: KeyboardEvent) => {
+      if (!inputKeys.includes(e.key) || isFormTarget(e.target)) return;
+      // 矢印キーでのスクロールを止める
+      e.preventDefault();
+      keys.add(e.key);
+      applyInput();
+    }
+    const onKeyUp = (e: KeyboardEvent) => {
+      if (!inputKeys.includes(e.key)) return;
+      // 入力欄にフォーカスが移っても押しっぱなしにならないよう、離した時は常に反映する
+      if (!isFormTarget(e.target)) e.preventDefault();
+      keys.delete(e.key);
+      applyInput();
+    }
+    window.addEventListener('keydown', onKeyDown);
+    window.addEventListener('keyup', onKeyUp);
+    return () => {
+      window.removeEventListener('keydown', onKeyDown);
+      window.removeEventListener('keyup', onKeyUp);
+    }
+  }, [])
+
   /** スレッドの追加/更新 追加時はcurrentThreadId = null */
   const upsertThread = () => {
     if (glInstanceRef.current) {
@@ -24,11 +24,22 @@ export const schema = z.object({
   shot_way_num: zodNumber({ min: 1, max: 100 }),
   shot_speed: zodNumber({}),
   shot_interval: zodNumber({ min: 50, max: 1000 }),
-  shot_behavior: z.array(zodNumber({ min: 0, max: 6 })),
+  shot_behavior: z.array(zodNumber({ min: 0, max: 15 })),
   speed_change_per: zodNumber({ min: 0, max: 100 }),
   speed_change_interval: zodNumber({ min: 10, max: 100 }),
   iteration_ms: zodNumber({ min: 0, max: 10000 }), // スケジュールトータル時間
   x_coordinate: zodNumber({ min: 1, max: 450 }), // TODO: ストアの値でルールを更新
+  linear_spread: zodNumber({ min: 0, max: 360 }),
+  linear_center: zodNumber({ min: -360, max: 360 }),
+  linear_inclusive: z.boolean(),
+  linear_random: z.boolean(),
+  stack_layers: zodNumber({ min: 1, max: 32 }),
+  stack_speed_step: zodNumber({ min: -10, max: 10 }),
+  emitter_motion: zodNumber({ min: 0, max: 3 }),
+  emitter_radius_x: zodNumber({ min: 0, max: 800 }),
+  emitter_radius_y: zodNumber({ min: 0, max: 800 }),
+  emitter_period_x: zodNumber({ min: 1, max: 10000 }),
+  emitter_period_y: zodNumber({ min: 1, max: 10000 }),
   y_coordinate: zodNumber({ min: 1, max: 800 }), // TODO: ストアの値でルールを更新
   reflect_count: zodNumber({ min: 0, max: 3 }), // TODO: ストアの値でルールを更新
   start_at: zodNumber({ min: 0, max: 10000 }), // TODO: ストアの値でルールを更新
@@ -40,6 +51,29 @@ export const schema = z.object({
   disk_color: zodNumber({ min: 0, max: 8 }), // TODO: ストアの値でルールを更新
   gravity_direction: zodNumber({ min: 0, max: 3 }), // TODO: ストアの値でルールを更新
   gravity_change_per: zodNumber({ min: 0, max: 100 }), // TODO: ストアの値でルールを更新
+  homing_turn_rate: zodNumber({ min: 0, max: 180 }),
+  homing_delay: zodNumber({ min: 0, max: 1000 }),
+  homing_duration: zodNumber({ min: 0, max: 1000 }),
+  path_curve: zodNumber({ min: 0, max: 1 }),
+  path_duration: zodNumber({ min: 1, max: 1000 }),
+  wave_amplitude: zodNumber({ min: 0, max: 400 }),
+  wave_frequency: zodNumber({ min: 0, max: 30 }),
+  wave_phase_step: zodNumber({ min: -360, max: 360 }),
+  split_after: zodNumber({ min: 0, max: 1000 }),
+  split_on_wall: z.boolean(),
+  emit_interval: zodNumber({ min: 1, max: 1000 }),
+  emit_max_shots: zodNumber({ min: 0, max: 64 }),
+  curvy_length: zodNumber({ min: 1, max: 300 }),
+  orbit_radius: zodNumber({ min: 0, max: 1000 }),
+  orbit_radius_speed: zodNumber({ min: -100, max: 100 }),
+  orbit_angular_speed: zodNumber({ min: -360, max: 360 }),
+  orbit_release: zodNumber({ min: 0, max: 10000 }),
+  orbit_follow: z.boolean(),
+  wrap_edges: zodNumber({ min: 0, max: 15 }),
+  wrap_count: zodNumber({ min: 0, max: 100 }),
+  curve_angular_velocity: zodNumber({ min: -360, max: 360 }),
+  curve_angular_acceleration: zodNumber({ min: -360, max: 360 }),
+  curve_max_angular_velocity: zodNumber({ min: 0, max: 360 }),
 })
 .refine((values) => {
   if (values.sleep_interval <= values.sleep_timeout) {
@@ -213,6 +247,30 @@ export const GameMaker: React.FC<GameMakerProps> = (props) => {
                 </div>
               </div>
 
+              <div className="flex flex-col y-interval">
+                <span>扇状</span>
+                <div className="flex gap-4">
+                  <ZodExtendedInput
+                    label="広がり(°)"
+                    type='number'
+                    name='linear_spread'
+                  />
+                  <ZodExtendedInput
+                    label="中心方向(°)"
+                    type='number'
+                    name='linear_center'
+                  />
+                  <label className="flex items-center gap-1">
+                    <input type="checkbox" {...methods.register('linear_inclusive')} />
+                    両端に撃つ
+                  </label>
+                  <label className="flex items-center gap-1">
+                    <input type="checkbox" {...methods.register('linear_random')} />
+                    ランダム
+                  </label>
+                </div>
+              </div>
+
 
               <ZodExtendedInput
                 label="ショット間隔"
@@ -236,6 +294,50 @@ export const GameMaker: React.FC<GameMakerProps> = (props) => {
                 </div>
               </div>
 
+              <div className="flex flex-col y-interval">
+                <span>射出位置の動き</span>
+                <div className="flex gap-4">
+                  <div className={clsx("flex flex-col")}>
+                    <label>軌道</label>
+                    <select {...methods.register('emitter_motion', {
+                      valueAsNumber: true,
+                    })}
+                    className={clsx("focus:outline-0 focus:border-2 focus:border-emerald-200 dark:focus:border-emerald-400 border border-gray-200 bg-gray-50 dark:bg-gray-600 dark:border-gray-700 rounded-md p-1")}
+                    >
+                      <option value="0">固定</option>
+                      <option value="2">楕円</option>
+                      <option value="3">リサージュ</option>
+                    </select>
+                  </div>
+                  {(formValues?.emitter_motion === 2 || formValues?.emitter_motion === 3) &&
+                    <>
+                      <ZodExtendedInput
+                        label="X半径"
+                        type='number'
+                        name='emitter_radius_x'
+                      />
+                      <ZodExtendedInput
+                        label="Y半径"
+                        type='number'
+                        name='emitter_radius_y'
+                      />
+                      <ZodExtendedInput
+                        label="周期fr"
+                        type='number'
+                        name='emitter_period_x'
+                      />
+                    </>
+                  }
+                  {formValues?.emitter_motion === 3 &&
+                    <ZodExtendedInput
+                      label="Y周期fr"
+                      type='number'
+                      name='emitter_period_y'
+                    />
+                  }
+                </div>
+              </div>
+
               <div className="flex flex-col y-interval">
                 <span>スケジュール</span>
                 <div className="flex gap-4">
@@ -279,6 +381,22 @@ export const GameMaker: React.FC<GameMakerProps> = (props) => {
                 name='shot_speed'
               /> 
 
+              <div className="flex flex-col y-interval">
+                <span>重ね撃ち</span>
+                <div className="flex gap-4">
+                  <ZodExtendedInput
+                    label="層数"
+                    type='number'
+                    name='stack_layers'
+                  />
+                  <ZodExtendedInput
+                    label="層毎の速度差"
+                    type='number'
+                    name='stack_speed_step'
+                  />
+                </div>
+              </div>
+
             <div className={clsx("flex flex-col")}>
               <label>弾種</label>
               <select {...methods.register('disk_type', {
@@ -388,6 +506,204 @@ export const GameMaker: React.FC<GameMakerProps> = (props) => {
                       </div>
                     </div> 
                   }
+
+                  {formValues.shot_behavior.includes(7) &&
+                    <div className="flex flex-col y-interval">
+                      <span>誘導</span>
+                      <div className="flex gap-4">
+                        <ZodExtendedInput
+                          label="旋回角(度/fr)"
+                          type='number'
+                          name='homing_turn_rate'
+                        />
+                        <ZodExtendedInput
+                          label="開始fr"
+                          type='number'
+                          name='homing_delay'
+                        />
+                        <ZodExtendedInput
+                          label="誘導fr(0で無制限)"
+                          type='number'
+                          name='homing_duration'
+                        />
+                      </div>
+                    </div>
+                  }
+
+                  {formValues.shot_behavior.includes(8) &&
+                    <div className="flex flex-col y-interval">
+                      <span>パス</span>
+                      <div className="flex gap-4">
+                        <div className={clsx("flex flex-col")}>
+                          <label>曲線</label>
+                          <select {...methods.register('path_curve', {
+                            valueAsNumber: true,
+                          })}
+                          className={clsx("focus:outline-0 focus:border-2 focus:border-emerald-200 dark:focus:border-emerald-400 border border-gray-200 bg-gray-50 dark:bg-gray-600 dark:border-gray-700 rounded-md p-1")}
+                          >
+                            <option value="0">ベジェ</option>
+                            <option value="1">Catmull-Rom</option>
+                          </select>
+                        </div>
+                        <ZodExtendedInput
+                          label="軌道fr"
+                          type='number'
+                          name='path_duration'
+                        />
+                      </div>
+                    </div>
+                  }
+
+                  {formValues.shot_behavior.includes(9) &&
+                    <div className="flex flex-col y-interval">
+                      <span>揺れ</span>
+                      <div className="flex gap-4">
+                        <ZodExtendedInput
+                          label="振幅px"
+                          type='number'
+                          name='wave_amplitude'
+                        />
+                        <ZodExtendedInput
+                          label="周波数Hz"
+                          type='number'
+                          name='wave_frequency'
+                        />
+                        <ZodExtendedInput
+                          label="WAY毎の位相ずれ(度)"
+                          type='number'
+                          name='wave_phase_step'
+                        />
+                      </div>
+                    </div>
+                  }
+
+                  {formValues.shot_behavior.includes(10) &&
+                    <div className="flex flex-col y-interval">
+                      <span>分裂</span>
+                      <div className="flex gap-4">
+                        <ZodExtendedInput
+                          label="分裂fr(0で時間分裂なし)"
+                          type='number'
+                          name='split_after'
+                        />
+                        <label className="flex items-center gap-1">
+                          <input type="checkbox" {...methods.register('split_on_wall')} />
+                          壁で分裂
+                        </label>
+                      </div>
+                    </div>
+                  }
+
+                  {formValues.shot_behavior.includes(11) &&
+                    <div className="flex flex-col y-interval">
+                      <span>発射台</span>
+                      <div className="flex gap-4">
+                        <ZodExtendedInput
+                          label="発射間隔fr"
+                          type='number'
+                          name='emit_interval'
+                        />
+                        <ZodExtendedInput
+                          label="最大発射回数"
+                          type='number'
+                          name='emit_max_shots'
+                        />
+                      </div>
+                    </div>
+                  }
+
+                  {formValues.shot_behavior.includes(12) &&
+                    <div className="flex flex-col y-interval">
+                      <span>曲がるレーザー</span>
+                      <div className="flex gap-4">
+                        <ZodExtendedInput
+                          label="長さfr"
+                          type='number'
+                          name='curvy_length'
+                        />
+                      </div>
+                    </div>
+                  }
+
+                  {formValues.shot_behavior.includes(13) &&
+                    <div className="flex flex-col y-interval">
+                      <span>回転</span>
+                      <div className="flex gap-4">
+                        <ZodExtendedInput
+                          label="初期半径"
+                          type='number'
+                          name='orbit_radius'
+                        />
+                        <ZodExtendedInput
+                          label="半径変化/fr"
+                          type='number'
+                          name='orbit_radius_speed'
+                        />
+                        <ZodExtendedInput
+                          label="回転角(度/fr)"
+                          type='number'
+                          name='orbit_angular_speed'
+                        />
+                        <ZodExtendedInput
+                          label="離脱fr(0で離脱なし)"
+                          type='number'
+                          name='orbit_release'
+                        />
+                        <label className="flex items-center gap-1">
+                          <input type="checkbox" {...methods.register('orbit_follow')} />
+                          発射点に追従
+                        </label>
+                      </div>
+                    </div>
+                  }
+
+                  {formValues.shot_behavior.includes(14) &&
+                    <div className="flex flex-col y-interval">
+                      <span>ループ</span>
+                      <div className="flex gap-4">
+                        <div className={clsx("flex flex-col")}>
+                          <label>ループする辺</label>
+                          <select {...methods.register('wrap_edges', {
+                            valueAsNumber: true,
+                          })}
+                          className={clsx("focus:outline-0 focus:border-2 focus:border-emerald-200 dark:focus:border-emerald-400 border border-gray-200 bg-gray-50 dark:bg-gray-600 dark:border-gray-700 rounded-md p-1")}
+                          >
+                            <option value="15">全辺</option>
+                            <option value="3">左右</option>
+                            <option value="12">上下</option>
+                          </select>
+                        </div>
+                        <ZodExtendedInput
+                          label="ループ回数(0で無制限)"
+                          type='number'
+                          name='wrap_count'
+                        />
+                      </div>
+                    </div>
+                  }
+
+                  {formValues.shot_behavior.includes(15) &&
+                    <div className="flex flex-col y-interval">
+                      <span>曲がる</span>
+                      <div className="flex gap-4">
+                        <ZodExtendedInput
+                          label="回転角(度/fr)"
+                          type='number'
+                          name='curve_angular_velocity'
+                        />
+                        <ZodExtendedInput
+                          label="回転角の変化(度/fr)"
+                          type='number'
+                          name='curve_angular_acceleration'
+                        />
+                        <ZodExtendedInput
+                          label="回転角の上限(0で上限なし)"
+                          type='number'
+                          name='curve_max_angular_velocity'
+                        />
+                      </div>
+                    </div>
+                  }
                 </>
               } 
 
@@ -27,4 +27,50 @@ export const gameStateAtom = atom({
   disk_color: 0,
   gravity_direction: 0,
   gravity_change_per: 10,
+  homing_turn_rate: 2,
+  homing_delay: 0,
+  homing_duration: 0,
+  path_curve: 0,
+  path_duration: 120,
+  wave_amplitude: 20,
+  wave_frequency: 1,
+  wave_phase_step: 0,
+  split_after: 60,
+  split_on_wall: false,
+  // 分裂弾の子弾設定
+  split: {
+    shot_type: 1,
+    shot_way_num: 8,
+    shot_speed: 2,
+  },
+  emit_interval: 10,
+  emit_max_shots: 10,
+  // 発射台弾の子弾設定
+  emit: {
+    shot_type: 1,
+    shot_way_num: 4,
+    shot_speed: 1,
+  },
+  curvy_length: 30,
+  orbit_radius: 0,
+  orbit_radius_speed: 1,
+  orbit_angular_speed: 2,
+  orbit_release: 0,
+  orbit_follow: false,
+  wrap_edges: 15,
+  wrap_count: 0,
+  curve_angular_velocity: 1,
+  curve_angular_acceleration: 0,
+  curve_max_angular_velocity: 0,
+  linear_spread: 100,
+  linear_center: 0,
+  linear_inclusive: false,
+  linear_random: false,
+  stack_layers: 1,
+  stack_speed_step: 0.3,
+  emitter_motion: 0,
+  emitter_radius_x: 100,
+  emitter_radius_y: 100,
+  emitter_period_x: 240,
+  emitter_period_y: 240,
 });
\ No newline at end of file
@@ -0,0 +1,72 @@
+//! 4,096弾が埋まった状態での 総当たり vs 一様グリッド の比較
+//! `cargo bench -p danmaku-core`
+
+use criterion::{ black_box, criterion_group, criterion_main, Criterion };
+use danmaku_core::{ Disk, DiskColor, DiskPool, DiskType, Rng, ShotBehavior, SpatialGrid, SpawnSpec, DISK_NUM };
+use danmaku_core::grid::{ brute_force_radius, DEFAULT_CELL_SIZE };
+
+const WIDTH: f64 = 800.;
+const HEIGHT: f64 = 800.;
+
+fn full_field() -> DiskPool {
+  let mut rng = Rng::new(1);
+  let mut pool = DiskPool::new(DISK_NUM as usize);
+  let disks = (0..DISK_NUM)
+    .map(|_| {
+      let x = rng.gen_range(0., WIDTH);
+      let y = rng.gen_range(0., HEIGHT);
+      Disk::new(x, y, DiskType::Oval, 10., 0., 0., DiskColor::Red)
+    })
+    .collect::<Vec<_>>();
+  pool.spawn_group(SpawnSpec::new(vec![ShotBehavior::Normal]), disks);
+  pool
+}
+
+/**
+ * 当たり判定を行う対象(自機/敵/障害物などを想定)
+ */
+fn colliders(num: usize) -> Vec<(f64, f64, f64)> {
+  let mut rng = Rng::new(2);
+  (0..num)
+    .map(|_| (rng.gen_range(0., WIDTH), rng.gen_range(0., HEIGHT), 20.))
+    .collect()
+}
+
+fn bench_grid(c: &mut Criterion) {
+  let disks = full_field();
+  let mut out = vec![];
+
+  for &num in &[1, 64] {
+    let targets = colliders(num);
+    let mut group = c.benchmark_group(format!("{}_colliders", num));
+
+    group.bench_function("brute_force", |b| {
+      b.iter(|| {
+        let mut hits = 0;
+        for &(x, y, r) in &targets {
+          brute_force_radius(black_box(&disks), x, y, r, &mut out);
+          hits += out.len();
+        }
+        hits
+      })
+    });
+
+    let mut grid = SpatialGrid::new(WIDTH, HEIGHT, DEFAULT_CELL_SIZE);
+    group.bench_function("grid_with_rebuild", |b| {
+      b.iter(|| {
+        grid.rebuild(black_box(&disks));
+        let mut hits = 0;
+        for &(x, y, r) in &targets {
+          grid.query_radius(&disks, x, y, r, &mut out);
+          hits += out.len();
+        }
+        hits
+      })
+    });
+
+    group.finish();
+  }
+}
+
+criterion_group!(benches, bench_grid);
+criterion_main!(benches);
@@ -0,0 +1,102 @@
+use super::MILLI_SECONDS;
+
+/**
+ * 固定タイムステップ
+ * 描画フレームの経過時間(ms)を貯めて、必要な数だけシミュレーションのtickを進める
+ * リフレッシュレートによらず1秒あたりのtick数は一定になる
+ */
+#[derive(Debug, Clone)]
+pub struct FixedTimestep {
+  tick_rate: u32,        // 1秒あたりのtick数
+  tick_ms: f64,          // 1tickあたりのms
+  max_catch_up: u32,     // 1回のadvanceで進める最大tick数
+  accumulator: f64,      // 未消化の経過時間(ms)
+  last_time: Option<f64>,
+}
+
+impl FixedTimestep {
+  pub fn new(tick_rate: u32, max_catch_up: u32) -> Self {
+    let tick_rate = tick_rate.max(1);
+    FixedTimestep {
+      tick_rate,
+      tick_ms: MILLI_SECONDS as f64 / tick_rate as f64,
+      max_catch_up,
+      accumulator: 0.,
+      last_time: None,
+    }
+  }
+
+  pub fn tick_rate(&self) -> u32 {
+    self.tick_rate
+  }
+
+  /**
+   * 現在時刻(ms)までに進めるべきtick数を返す
+   * 初回/時刻が巻き戻った場合(一時停止からの再開など)は基準時刻の設定のみ行う
+   * 上限を超える遅延(タブの非表示など)は追いつかずに切り捨てる
+   */
+  pub fn advance(&mut self, time: f64) -> u32 {
+    let last_time = match self.last_time {
+      Some(last_time) if time >= last_time => last_time,
+      _ => {
+        self.last_time = Some(time);
+        self.accumulator = 0.;
+        return 0;
+      },
+    };
+    self.last_time = Some(time);
+    self.accumulator += time - last_time;
+
+    let ticks = (self.accumulator / self.tick_ms).floor() as u32;
+    if ticks > self.max_catch_up {
+      self.accumulator = 0.;
+      return self.max_catch_up;
+    }
+    self.accumulator -= ticks as f64 * self.tick_ms;
+    ticks
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  /**
+   * 指定のリフレッシュレートで1秒間描画した時のtick数
+   */
+  fn ticks_in_one_sec(clock: &mut FixedTimestep, refresh_rate: u32) -> u32 {
+    let frame_ms = 1000. / refresh_rate as f64;
+    clock.advance(0.);
+    (1..=refresh_rate)
+      .map(|i| clock.advance(frame_ms * i as f64))
+      .sum()
+  }
+
+  #[test]
+  fn test_tick_count_is_independent_of_refresh_rate() {
+    for &refresh_rate in &[30, 60, 75, 120, 144, 240] {
+      let mut clock = FixedTimestep::new(60, 10);
+      let ticks = ticks_in_one_sec(&mut clock, refresh_rate);
+      assert!((59..=60).contains(&ticks), "{}Hz -> {} ticks", refresh_rate, ticks);
+    }
+  }
+
+  #[test]
+  fn test_catch_up_is_capped() {
+    let mut clock = FixedTimestep::new(60, 5);
+    clock.advance(0.);
+    assert_eq!(clock.advance(1000.), 5);
+    // 切り捨てた分は持ち越さない
+    assert_eq!(clock.advance(1010.), 0);
+    assert_eq!(clock.advance(1020.), 1);
+  }
+
+  #[test]
+  fn test_time_rewind_resets_clock() {
+    let mut clock = FixedTimestep::new(100, 10);
+    assert_eq!(clock.advance(500.), 0);
+    assert_eq!(clock.advance(530.), 3);
+    assert_eq!(clock.advance(0.), 0);
+    assert_eq!(clock.advance(20.), 2);
+  }
+}
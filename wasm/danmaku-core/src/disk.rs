@@ -1,5 +1,3 @@
-use super::shot::{ ShotBehavior };
-use super::vec2d::{ Vec2d };
 
 // TODO: 仮
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -22,7 +20,7 @@ pub fn resolve_disk_type(disk_type: u32) -> DiskType {
   }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum DiskColor {
   Red,
   Orange,
@@ -50,101 +48,71 @@ pub fn resolve_disk_color(disk_color: u32) -> DiskColor {
   }
 }
 
-#[derive(Debug, Clone)]
+/**
+ * Disk生成時のパラメータ
+ * 生成後の状態はDiskPool側に列毎(SoA)で保持する
+ */
+#[derive(Debug, Copy, Clone)]
 pub struct Disk {
-    pub age: u32, // exist age
-    pub reflect_count: Option<u32>, // reflect count
     pub x: f64,   // x-coordinate
     pub y: f64,   // y-coordinate
     pub speed: f64, // speed
     pub angle: f64, //
-    pub vec2d: Vec2d, // moving velocity
-    pub behavior: Vec<ShotBehavior>, // shot behavior.
     pub disk_type: DiskType, // disk type.
     pub disk_size: f64, // disk size.
-    pub img_source: Option<String>, // image source.
-    pub sleep_time: i32,
     pub disk_color: DiskColor,
+    pub phase: f64, // 揺れ弾の初期位相(rad)
 }
 
 impl Disk {
     pub fn new(
       x: f64,
       y: f64,
-      behavior: Vec<ShotBehavior>,
       disk_type: DiskType,
       disk_size: f64,
       angle: f64,
       speed: f64,
       disk_color: DiskColor,
     ) -> Self {
-        let vec2d = Vec2d::new(angle, speed);
-        let reflect_count = behavior
-          .iter()
-          .find_map(|&sb| match sb {
-            ShotBehavior::Reflect(num) => num,
-            _ => None,
-          });
         Self {
-          age: 0,
-          reflect_count,
           x,
           y,
           speed,
           angle,
-          vec2d,
-          behavior, 
           disk_type,
           disk_size,
-          img_source: None,
-          sleep_time: 0,
           disk_color,
+          phase: 0.,
         }
     }
 
-    pub fn gain_age(&mut self, by: u32) {
-        self.age = self.age + by;
+    /**
+     * 揺れ弾の初期位相を指定
+     */
+    pub fn with_phase(mut self, phase: f64) -> Self {
+        self.phase = phase;
+        self
     }
 }
 
 #[cfg(test)]
 mod test {
   use super::*;
-  use std::f64::consts::PI;
 
   #[test]
   fn test_disk_type_resolve() {
-    let results = vec![
-      DiskType::Oval,     
+    let results = [
+      DiskType::Oval,
       DiskType::Dot,
       DiskType::Circle,
       DiskType::Orb,
       DiskType::Arrow,
-      DiskType::Oval,     
+      DiskType::Oval,
     ];
-    let nums = (0..=5).into_iter().collect::<Vec<_>>();
+    let nums = (0..=5).collect::<Vec<_>>();
     for (i, &num) in nums.iter().enumerate() {
       let disk_type = resolve_disk_type(num);
       assert_eq!(results[i], disk_type);
     }
   }
-
-  #[test]
-  fn test_gain_age() {
-    let disk_type = resolve_disk_type(0);
-    let mut disk = Disk::new(
-      10.,
-      10.,
-      vec![ShotBehavior::Normal],
-      disk_type,
-      4.,
-      PI * 90. / 180.,
-      10.,
-      DiskColor::Red,
-    );
-    disk.gain_age(1);
-    assert_eq!(disk.age, 1);
-    disk.gain_age(5);
-    assert_eq!(disk.age, 6);
-  }
-}
\ No newline at end of file
+}
@@ -0,0 +1,104 @@
+use super::laser::{ Laser };
+use super::rng::{ Rng };
+use super::shot::{ HomingTarget, ShotType };
+use super::setting::{ Setting };
+use super::pool::{ DiskPool, SpawnSpec };
+use super::vec2d::{ Vec2d };
+
+#[derive(Debug, Clone)]
+pub struct EventThread {
+  pub id: u32,
+  pub iter: u32, // スレッド単位での実行時間
+  pub setting: Setting,
+  rng: Option<Rng>, // Thread固有の乱数(thread_seed指定時のみ)
+  aim: f64, // 自機狙いの現在の狙い(rad、aim_burst回の発射ごとに更新)
+}
+
+impl EventThread {
+  pub fn new(id: u32, setting: Setting) -> Self {
+    let rng = setting.thread_seed.map(Rng::new);
+    EventThread {
+      id,
+      iter: 0,
+      setting,
+      rng,
+      aim: 0.,
+    }
+  }
+
+  /**
+   * setting更新(thread_seed指定時は乱数列もリセット)
+   */
+  pub fn update_setting(&mut self, setting: Setting) {
+    self.rng = setting.thread_seed.map(Rng::new);
+    self.setting = setting;
+  }
+
+  /**
+   * ショット種別毎にDiskPoolへのデータ割り当て(Laserの場合はlasersへ追加)
+   * 乱数はThread固有のものがあればそれを、なければ引数のものを使う
+   * tick_rateは秒単位の設定値(揺れ弾の周波数など)をtick単位に直すのに使う
+   * timeはスケジュール上の時刻(tick)で、発射点の動きの評価に使う
+   * playerは自機狙いの目標にする自機座標
+   */
+  pub fn spawn_disks(&mut self, disks: &mut DiskPool, lasers: &mut Vec<Laser>, rng: &mut Rng, tick_rate: u32, time: u32, player: (f64, f64)) {
+    self.iter += 1;
+    let angle_offset = self.setting.degree_change_by * self.iter as f64;
+    let Vec2d { x, y } = self.setting.origin(time);
+
+    if let ShotType::Laser = self.setting.shot_type {
+      lasers.extend(
+        self.setting.pattern()
+          .laser_angles(angle_offset)
+          .into_iter()
+          .map(|angle| Laser::new(x, y, angle, self.setting.laser))
+      );
+      return;
+    }
+
+    let mut thread_rng = self.rng.take();
+    let rng = thread_rng.as_mut().unwrap_or(rng);
+
+    let angle_offset = match self.setting.shot_type {
+      ShotType::Aimed => self.aim_at(x, y, player, rng),
+      ShotType::Rotate | ShotType::Swirl => self.setting.rotate_speed.to_radians() * time as f64,
+      _ => angle_offset,
+    };
+    // 各弾種共通設定
+    let shot_behavior = self.setting.behavior(tick_rate);
+    let timeline = self.setting.timeline(tick_rate);
+    let children = self.setting.children(tick_rate);
+    let new_disks = self.setting.pattern().generate(x, y, angle_offset, rng);
+
+    self.rng = thread_rng;
+
+    // DiskPoolの空きスロットに順次割り当てる(満杯時はプールのOverflowPolicyに従う)
+    // 挙動は同じ発射イベントのDisk間で共有する
+    let spec = SpawnSpec::new(shot_behavior)
+      .with_priority(self.setting.priority)
+      .with_children(children)
+      .with_timeline(timeline);
+    // 回転弾が発射点に追従する場合は、発射元を覚えておく(中心はSimulationが毎tick更新する)
+    let spec = if self.setting.orbit_follow { spec.with_emitter(self.id, Vec2d { x, y }) } else { spec };
+    disks.spawn_group(spec, new_disks);
+  }
+
+  /**
+   * (x, y)から目標への向き(rad)
+   * aim_burst回の発射の間は最初に狙った向きを使い続ける
+   */
+  fn aim_at(&mut self, x: f64, y: f64, player: (f64, f64), rng: &mut Rng) -> f64 {
+    if (self.iter - 1).is_multiple_of(self.setting.aim_burst) {
+      let (tx, ty) = match self.setting.aim_target {
+        HomingTarget::Player => player,
+        HomingTarget::Point(x, y) => (x, y),
+      };
+      let jitter = self.setting.aim_jitter.to_radians();
+      self.aim = (tx - x).atan2(ty - y);
+      if jitter > 0. {
+        self.aim += rng.gen_range(-jitter, jitter);
+      }
+    }
+    self.aim
+  }
+}
@@ -0,0 +1,202 @@
+use super::pool::{ DiskPool };
+use super::rectanble::{ Rectangle };
+
+pub const DEFAULT_CELL_SIZE: f64 = 32.;
+
+/**
+ * Disk検索用の一様グリッド
+ * 毎フレームDiskの座標から再構築し、近傍のDiskだけを当たり判定の候補にする
+ * セルの中身はCSR形式(cell_start + indices)で持つので再構築時にアロケーションしない
+ */
+#[derive(Debug, Clone)]
+pub struct SpatialGrid {
+  cell_size: f64,
+  cols: usize,
+  rows: usize,
+  cell_start: Vec<usize>, // セルiの要素はindices[cell_start[i]..cell_start[i + 1]]
+  indices: Vec<usize>,    // Diskのスロット番号
+  cursor: Vec<usize>,     // 再構築時の書き込み位置
+  slot_cells: Vec<Option<usize>>, // 再構築時のスロット毎のセル番号
+  max_radius: f64,        // 登録されたDiskの最大半径(検索範囲の拡張用)
+}
+
+impl SpatialGrid {
+  pub fn new(width: f64, height: f64, cell_size: f64) -> Self {
+    let cols = ((width / cell_size).ceil() as usize).max(1);
+    let rows = ((height / cell_size).ceil() as usize).max(1);
+    SpatialGrid {
+      cell_size,
+      cols,
+      rows,
+      cell_start: vec![0; cols * rows + 1],
+      indices: vec![],
+      cursor: vec![],
+      slot_cells: vec![],
+      max_radius: 0.,
+    }
+  }
+
+  /**
+   * 座標 -> セル座標(画面外はフチのセルに寄せる)
+   */
+  fn cell_of(&self, x: f64, y: f64) -> (usize, usize) {
+    // 負数は0に寄せるので、floorせずに切り捨てで良い
+    let col = (x / self.cell_size).max(0.) as usize;
+    let row = (y / self.cell_size).max(0.) as usize;
+    (col.min(self.cols - 1), row.min(self.rows - 1))
+  }
+
+  /**
+   * 生存しているDiskからグリッドを再構築する
+   */
+  pub fn rebuild(&mut self, disks: &DiskPool) {
+    let cell_num = self.cols * self.rows;
+    self.cell_start.iter_mut().for_each(|c| *c = 0);
+    self.max_radius = 0.;
+
+    // セル毎の個数を数える
+    let mut total = 0;
+    self.slot_cells.clear();
+    for i in 0..disks.capacity() {
+      let cell = if disks.is_alive(i) {
+        let (col, row) = self.cell_of(disks.x[i], disks.y[i]);
+        self.max_radius = self.max_radius.max(disks.disk_size[i] / 2.);
+        Some(row * self.cols + col)
+      } else {
+        None
+      };
+      if let Some(cell) = cell {
+        self.cell_start[cell + 1] += 1;
+        total += 1;
+      }
+      self.slot_cells.push(cell);
+    }
+    // 累積和で各セルの開始位置を求める
+    for i in 0..cell_num {
+      self.cell_start[i + 1] += self.cell_start[i];
+    }
+    // 開始位置から順に詰める
+    self.indices.resize(total, 0);
+    self.cursor.clear();
+    self.cursor.extend_from_slice(&self.cell_start);
+    for (i, cell) in self.slot_cells.iter().enumerate() {
+      if let Some(cell) = *cell {
+        self.indices[self.cursor[cell]] = i;
+        self.cursor[cell] += 1;
+      }
+    }
+  }
+
+  /**
+   * 矩形範囲に掛かるセルに入っているDiskのスロット番号を列挙する(候補のみ)
+   */
+  fn candidates(&self, left: f64, top: f64, right: f64, bottom: f64, out: &mut Vec<usize>) {
+    let (col_from, row_from) = self.cell_of(left, top);
+    let (col_to, row_to) = self.cell_of(right, bottom);
+    for row in row_from..=row_to {
+      let from = self.cell_start[row * self.cols + col_from];
+      let to = self.cell_start[row * self.cols + col_to + 1];
+      out.extend_from_slice(&self.indices[from..to]);
+    }
+  }
+
+  /**
+   * 点(x, y)から半径radius以内に掛かるDiskのスロット番号
+   */
+  pub fn query_radius(&self, disks: &DiskPool, x: f64, y: f64, radius: f64, out: &mut Vec<usize>) {
+    out.clear();
+    let r = radius + self.max_radius;
+    self.candidates(x - r, y - r, x + r, y + r, out);
+    out.retain(|&i| disks.is_alive(i) && overlaps_circle(disks, i, x, y, radius));
+  }
+
+  /**
+   * 矩形に掛かるDiskのスロット番号
+   */
+  pub fn query_rect(&self, disks: &DiskPool, rect: &Rectangle, out: &mut Vec<usize>) {
+    out.clear();
+    let r = self.max_radius;
+    self.candidates(rect.x - r, rect.y - r, rect.x + rect.width + r, rect.y + rect.height + r, out);
+    out.retain(|&i| disks.is_alive(i) && rect.overlaps_circle(disks.x[i], disks.y[i], disks.disk_size[i] / 2.));
+  }
+}
+
+/**
+ * 総当たりでの半径検索(ベンチマーク/検証用)
+ */
+pub fn brute_force_radius(disks: &DiskPool, x: f64, y: f64, radius: f64, out: &mut Vec<usize>) {
+  out.clear();
+  for i in 0..disks.capacity() {
+    if disks.is_alive(i) && overlaps_circle(disks, i, x, y, radius) {
+      out.push(i);
+    }
+  }
+}
+
+fn overlaps_circle(disks: &DiskPool, i: usize, x: f64, y: f64, radius: f64) -> bool {
+  let r = radius + disks.disk_size[i] / 2.;
+  let dx = disks.x[i] - x;
+  let dy = disks.y[i] - y;
+  dx * dx + dy * dy < r * r
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::disk::{ Disk, DiskColor, DiskType };
+  use crate::rng::{ Rng };
+  use crate::pool::{ SpawnSpec };
+  use crate::shot::{ ShotBehavior };
+
+  fn random_disks(num: usize) -> DiskPool {
+    let mut rng = Rng::new(3);
+    let mut pool = DiskPool::new(num);
+    let disks = (0..num)
+      .map(|_| {
+        // 画面外も含めて配置
+        let x = rng.gen_range(-50., 850.);
+        let y = rng.gen_range(-50., 850.);
+        Disk::new(x, y, DiskType::Oval, rng.gen_range(2., 20.), 0., 0., DiskColor::Red)
+      })
+      .collect::<Vec<_>>();
+    pool.spawn_group(SpawnSpec::new(vec![ShotBehavior::Normal]), disks);
+    // 空きスロットも混ぜる
+    for i in (0..num).step_by(3) {
+      pool.despawn(i);
+    }
+    pool
+  }
+
+  #[test]
+  fn test_query_radius_matches_brute_force() {
+    let disks = random_disks(4_096);
+    let mut grid = SpatialGrid::new(800., 800., 32.);
+    grid.rebuild(&disks);
+    let mut expected = vec![];
+    let mut actual = vec![];
+    for &(x, y, r) in &[(400., 400., 30.), (0., 0., 50.), (790., 10., 5.), (-40., 900., 60.)] {
+      brute_force_radius(&disks, x, y, r, &mut expected);
+      grid.query_radius(&disks, x, y, r, &mut actual);
+      actual.sort_unstable();
+      assert_eq!(expected, actual);
+    }
+  }
+
+  #[test]
+  fn test_query_rect() {
+    let disks = random_disks(4_096);
+    let mut grid = SpatialGrid::new(800., 800., 32.);
+    grid.rebuild(&disks);
+    let rect = Rectangle::new(100., 200., 150., 80.);
+    let expected = disks
+      .iter()
+      .filter(|d| rect.overlaps_circle(d.x, d.y, d.disk_size / 2.))
+      .map(|d| d.handle.index as usize)
+      .collect::<Vec<_>>();
+    let mut actual = vec![];
+    grid.query_rect(&disks, &rect, &mut actual);
+    actual.sort_unstable();
+    assert!(!expected.is_empty());
+    assert_eq!(expected, actual);
+  }
+}
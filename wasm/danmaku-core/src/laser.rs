@@ -0,0 +1,185 @@
+use super::disk::{ DiskColor };
+
+/**
+ * レーザーの状態
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LaserPhase {
+  Warning,  // 予告線(細く、当たり判定なし)
+  Active,   // 照射中(当たり判定あり)
+  Finished, // 照射終了(削除対象)
+}
+
+/**
+ * レーザーの形状/照射時間(Threadの設定値)
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LaserSpec {
+  pub length: f64, // 長さ
+  pub width: f64,  // 照射中の太さ
+  pub warning_ticks: u32, // 予告線のtick数
+  pub active_ticks: u32,  // 照射するtick数
+  pub rotation: f64,      // 1tickあたりの回転量(rad)
+  pub color: DiskColor,
+}
+
+/**
+ * 発射点から角度angle方向に伸びる直線レーザー
+ * 当たり判定は線分を太さ分膨らませたカプセル形状
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Laser {
+  pub x: f64,     // 発射点
+  pub y: f64,
+  pub angle: f64, // 向き(rad)
+  pub spec: LaserSpec,
+  pub age: u32,
+  pub hit: bool, // 自機に当たり済み(1本につき1回のみ被弾)
+}
+
+impl Laser {
+  pub fn new(x: f64, y: f64, angle: f64, spec: LaserSpec) -> Self {
+    Laser {
+      x,
+      y,
+      angle,
+      spec,
+      age: 0,
+      hit: false,
+    }
+  }
+
+  pub fn phase(&self) -> LaserPhase {
+    if self.age < self.spec.warning_ticks {
+      LaserPhase::Warning
+    } else if self.age < self.spec.warning_ticks + self.spec.active_ticks {
+      LaserPhase::Active
+    } else {
+      LaserPhase::Finished
+    }
+  }
+
+  /**
+   * 1tick進める(予告線の間も回転する)
+   */
+  pub fn update(&mut self) {
+    self.age += 1;
+    self.angle += self.spec.rotation;
+  }
+
+  /**
+   * 終点の座標
+   */
+  pub fn end(&self) -> (f64, f64) {
+    let (sin, cos) = self.angle.sin_cos();
+    (self.x + sin * self.spec.length, self.y + cos * self.spec.length)
+  }
+
+  /**
+   * 円(中心x, y/半径radius)との当たり判定(照射中のみ)
+   */
+  pub fn hit_test(&self, x: f64, y: f64, radius: f64) -> bool {
+    if self.phase() != LaserPhase::Active { return false }
+    let r = self.spec.width / 2. + radius;
+    let (ex, ey) = self.end();
+    distance_sq_to_segment(x, y, self.x, self.y, ex, ey) < r * r
+  }
+
+  /**
+   * 中心線上で(x, y)に最も近い点
+   */
+  pub fn closest_point(&self, x: f64, y: f64) -> (f64, f64) {
+    let (ex, ey) = self.end();
+    closest_point_on_segment(x, y, self.x, self.y, ex, ey)
+  }
+}
+
+/**
+ * 線分(ax, ay)-(bx, by)上で点(px, py)に最も近い点
+ */
+pub fn closest_point_on_segment(px: f64, py: f64, ax: f64, ay: f64, bx: f64, by: f64) -> (f64, f64) {
+  let (dx, dy) = (bx - ax, by - ay);
+  let len_sq = dx * dx + dy * dy;
+  let t = if len_sq == 0. {
+    0.
+  } else {
+    (((px - ax) * dx + (py - ay) * dy) / len_sq).clamp(0., 1.)
+  };
+  (ax + dx * t, ay + dy * t)
+}
+
+/**
+ * 点(px, py)から線分(ax, ay)-(bx, by)までの距離の2乗
+ */
+pub fn distance_sq_to_segment(px: f64, py: f64, ax: f64, ay: f64, bx: f64, by: f64) -> f64 {
+  let (cx, cy) = closest_point_on_segment(px, py, ax, ay, bx, by);
+  (cx - px).powi(2) + (cy - py).powi(2)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn laser() -> Laser {
+    // (0, 0)から真下に長さ100、太さ10
+    Laser::new(0., 0., 0., LaserSpec {
+      length: 100.,
+      width: 10.,
+      warning_ticks: 2,
+      active_ticks: 3,
+      rotation: 0.,
+      color: DiskColor::Red,
+    })
+  }
+
+  #[test]
+  fn test_phases() {
+    let mut laser = laser();
+    let mut phases = vec![];
+    for _ in 0..6 {
+      phases.push(laser.phase());
+      laser.update();
+    }
+    assert_eq!(phases, vec![
+      LaserPhase::Warning,
+      LaserPhase::Warning,
+      LaserPhase::Active,
+      LaserPhase::Active,
+      LaserPhase::Active,
+      LaserPhase::Finished,
+    ]);
+  }
+
+  #[test]
+  fn test_capsule_hit() {
+    let mut laser = laser();
+    // 予告線の間は当たらない
+    assert!(!laser.hit_test(0., 50., 1.));
+    laser.age = 2;
+    assert!(laser.hit_test(0., 50., 1.));
+    assert!(laser.hit_test(5.5, 50., 1.));
+    assert!(!laser.hit_test(6.5, 50., 1.));
+    // 両端は丸く膨らむ
+    assert!(laser.hit_test(0., 105., 1.));
+    assert!(!laser.hit_test(5., 105., 1.));
+    assert!(!laser.hit_test(0., -7., 1.));
+  }
+
+  #[test]
+  fn test_closest_point() {
+    let laser = laser();
+    assert_eq!(laser.closest_point(5., 50.), (0., 50.));
+    // 線分の外側は端点
+    assert_eq!(laser.closest_point(3., 120.), (0., 100.));
+    assert_eq!(laser.closest_point(-3., -20.), (0., 0.));
+  }
+
+  #[test]
+  fn test_rotation() {
+    let mut laser = laser();
+    laser.spec.rotation = std::f64::consts::FRAC_PI_2;
+    laser.update();
+    let (ex, ey) = laser.end();
+    assert!((ex - 100.).abs() < 1e-9 && ey.abs() < 1e-9);
+  }
+}
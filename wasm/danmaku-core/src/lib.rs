@@ -0,0 +1,53 @@
+//! 弾幕シミュレーション本体.
+//!
+//! web-sysに依存しないため、ブラウザ外(ネイティブ)でもビルド/テストできる.
+//! 描画やDOM操作はwasmクレート側の`Screen`が担当する.
+
+pub mod vec2d;
+pub mod clock;
+pub mod rectanble;
+pub mod player;
+pub mod laser;
+pub mod path;
+pub mod motion;
+pub mod grid;
+pub mod pool;
+pub mod rng;
+pub mod disk;
+pub mod shot;
+pub mod timeline;
+pub mod setting;
+pub mod schedule;
+pub mod event;
+pub mod event_thread;
+pub mod simulation;
+
+pub use clock::FixedTimestep;
+pub use disk::{ Disk, DiskType, DiskColor };
+pub use setting::{ Setting, SettingOptions };
+pub use grid::SpatialGrid;
+pub use pool::{ DiskPool, DiskHandle, DiskView, OverflowPolicy, SpawnSpec };
+pub use laser::{ Laser, LaserPhase, LaserSpec };
+pub use path::{ Path, PathCurve };
+pub use motion::{ EmitterMotion, Easing, Keyframe };
+pub use player::{ Player, PlayerEvent, PlayerInput };
+pub use rectanble::Rectangle;
+pub use rng::Rng;
+pub use shot::{ ShotBehavior, ShotType };
+pub use simulation::{ Simulation, ThreadId };
+pub use timeline::{ Stage, Timeline };
+pub use vec2d::Vec2d;
+
+pub const DEFAULT_TICK_RATE: u32 = 60; // 1秒あたりのtick数(SettingOptions.tick_rateで変更可)
+pub const DEFAULT_MAX_CATCH_UP_TICKS: u32 = 10; // 1描画フレームで進める最大tick数
+pub const MILLI_SECONDS: u32 = 1_000;
+pub const DISK_NUM: u32 = 4_096; // Diskプールの初期スロット数(SettingOptions.disk_capacityで変更可)
+
+/**
+ * tick rateをもとにインターバル(ms)をフレーム数に変換する
+ * ex) 60tick/s: 500ms -> 30fr
+ *               200ms -> 12fr
+ */
+pub fn convert_interval_to_frame(interval: u32, tick_rate: u32) -> f64 {
+    (tick_rate as f64) * ((interval as f64) / (MILLI_SECONDS as f64))
+}
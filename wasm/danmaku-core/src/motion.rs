@@ -0,0 +1,159 @@
+use super::vec2d::{ Vec2d };
+
+/**
+ * キーフレーム間の補間方法
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Easing {
+  Linear,
+  EaseIn,    // 加速しながら移動
+  EaseOut,   // 減速しながら移動
+  EaseInOut, // 加速してから減速
+}
+
+pub fn resolve_easing(num: u32) -> Easing {
+  match num {
+    1 => Easing::EaseIn,
+    2 => Easing::EaseOut,
+    3 => Easing::EaseInOut,
+    _ => Easing::Linear,
+  }
+}
+
+impl Easing {
+  /**
+   * 区間内の進み具合t(0..=1) -> 補間後の進み具合
+   */
+  pub fn apply(&self, t: f64) -> f64 {
+    let t = t.clamp(0., 1.);
+    match self {
+      Easing::Linear => t,
+      Easing::EaseIn => t * t,
+      Easing::EaseOut => t * (2. - t),
+      Easing::EaseInOut => t * t * (3. - 2. * t),
+    }
+  }
+}
+
+/**
+ * 指定時刻(tick)での発射点
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Keyframe {
+  pub time: u32,
+  pub x: f64,
+  pub y: f64,
+}
+
+/**
+ * 発射点の動き(Threadの設定値)
+ * 時刻はスケジュール上のtick数で、周期/位相以外の座標はpx
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum EmitterMotion {
+  Fixed, // 固定(x_coordinate, y_coordinate)
+  Waypoints(Vec<Keyframe>, Easing), // キーフレーム間を補間して移動(最初/最後のキーフレームの前後は留まる)
+  Orbit(f64, f64, u32, f64), // 基準点を中心に楕円軌道(X半径, Y半径, 周期tick, 初期位相rad)
+  Lissajous(f64, f64, u32, u32, f64), // 基準点を中心にリサージュ曲線(X振幅, Y振幅, X周期tick, Y周期tick, X軸の位相rad)
+}
+
+impl EmitterMotion {
+  /**
+   * Jsからの入力値([t1, x1, y1, t2, x2, y2, ...])からキーフレーム移動を生成する(時刻順に並べ替える)
+   */
+  pub fn waypoints_from_slice(values: &[f64], easing: Easing) -> Self {
+    let mut keyframes = values
+      .chunks_exact(3)
+      .map(|v| Keyframe { time: v[0].max(0.) as u32, x: v[1], y: v[2] })
+      .collect::<Vec<Keyframe>>();
+    keyframes.sort_by_key(|k| k.time);
+    EmitterMotion::Waypoints(keyframes, easing)
+  }
+
+  /**
+   * 時刻timeでの発射点
+   * (base_x, base_y)は固定/楕円/リサージュの基準点
+   */
+  pub fn position(&self, base_x: f64, base_y: f64, time: u32) -> Vec2d {
+    use std::f64::consts::PI;
+    let angle = |period: u32| 2. * PI * time as f64 / period.max(1) as f64;
+    match self {
+      EmitterMotion::Fixed => Vec2d { x: base_x, y: base_y },
+      EmitterMotion::Waypoints(keyframes, easing) => {
+        let (first, last) = match (keyframes.first(), keyframes.last()) {
+          (Some(first), Some(last)) => (first, last),
+          _ => return Vec2d { x: base_x, y: base_y },
+        };
+        if time <= first.time { return Vec2d { x: first.x, y: first.y } }
+        if time >= last.time { return Vec2d { x: last.x, y: last.y } }
+        // time < last.timeなので必ず見つかる
+        let next = keyframes.iter().position(|k| k.time > time).unwrap_or(keyframes.len() - 1);
+        let (from, to) = (keyframes[next - 1], keyframes[next]);
+        let t = easing.apply((time - from.time) as f64 / (to.time - from.time) as f64);
+        Vec2d {
+          x: from.x + (to.x - from.x) * t,
+          y: from.y + (to.y - from.y) * t,
+        }
+      },
+      EmitterMotion::Orbit(radius_x, radius_y, period, phase) => {
+        let theta = angle(*period) + phase;
+        Vec2d {
+          x: base_x + radius_x * theta.sin(),
+          y: base_y + radius_y * theta.cos(),
+        }
+      },
+      EmitterMotion::Lissajous(amplitude_x, amplitude_y, period_x, period_y, phase) => {
+        Vec2d {
+          x: base_x + amplitude_x * (angle(*period_x) + phase).sin(),
+          y: base_y + amplitude_y * angle(*period_y).sin(),
+        }
+      },
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn approx(a: Vec2d, x: f64, y: f64) -> bool {
+    (a.x - x).abs() < 1e-9 && (a.y - y).abs() < 1e-9
+  }
+
+  #[test]
+  fn test_waypoints_hold_and_interpolate() {
+    // 順不同で渡しても時刻順になる
+    let motion = EmitterMotion::waypoints_from_slice(&[100., 200., 0., 20., 0., 0., 60., 200., 100.], Easing::Linear);
+    assert!(approx(motion.position(0., 0., 0), 0., 0.));
+    assert!(approx(motion.position(0., 0., 20), 0., 0.));
+    assert!(approx(motion.position(0., 0., 40), 100., 50.));
+    assert!(approx(motion.position(0., 0., 80), 200., 50.));
+    assert!(approx(motion.position(0., 0., 500), 200., 0.));
+    // キーフレームなしは基準点
+    let motion = EmitterMotion::waypoints_from_slice(&[], Easing::Linear);
+    assert!(approx(motion.position(5., 6., 10), 5., 6.));
+  }
+
+  #[test]
+  fn test_easing() {
+    for &easing in &[Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut] {
+      assert_eq!(easing.apply(0.), 0.);
+      assert_eq!(easing.apply(1.), 1.);
+    }
+    assert!(Easing::EaseIn.apply(0.5) < 0.5);
+    assert!(Easing::EaseOut.apply(0.5) > 0.5);
+    assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+  }
+
+  #[test]
+  fn test_orbit_and_lissajous() {
+    let orbit = EmitterMotion::Orbit(100., 50., 40, 0.);
+    assert!(approx(orbit.position(400., 400., 0), 400., 450.));
+    assert!(approx(orbit.position(400., 400., 10), 500., 400.));
+    assert!(approx(orbit.position(400., 400., 40), 400., 450.));
+    // X:Y = 1:2の周期
+    let lissajous = EmitterMotion::Lissajous(100., 100., 40, 20, 0.);
+    assert!(approx(lissajous.position(0., 0., 5), 100. * std::f64::consts::FRAC_PI_4.sin(), 100.));
+    assert!(approx(lissajous.position(0., 0., 10), 100., 0.));
+  }
+}
@@ -0,0 +1,183 @@
+use super::vec2d::{ Vec2d };
+
+/**
+ * パス弾の曲線種別
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PathCurve {
+  Bezier,     // 3次ベジェ(発射点 + 制御点2つ + 終点)
+  CatmullRom, // Catmull-Romスプライン(発射点と3点を全て通る)
+}
+
+pub fn resolve_path_curve(num: u32) -> PathCurve {
+  match num {
+    1 => PathCurve::CatmullRom,
+    _ => PathCurve::Bezier,
+  }
+}
+
+/**
+ * 発射点を原点、発射方向を+yとしたローカル座標での軌道
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Path {
+  pub curve: PathCurve,
+  pub points: [Vec2d; 3], // 原点以降の3点
+}
+
+impl Path {
+  pub fn new(curve: PathCurve, points: [Vec2d; 3]) -> Self {
+    Path { curve, points }
+  }
+
+  /**
+   * Jsからの入力値([x1, y1, x2, y2, x3, y3])から生成する(足りない分は0)
+   */
+  pub fn from_slice(curve: PathCurve, values: &[f64]) -> Self {
+    let value = |i: usize| values.get(i).copied().unwrap_or(0.);
+    Path::new(curve, [
+      Vec2d { x: value(0), y: value(1) },
+      Vec2d { x: value(2), y: value(3) },
+      Vec2d { x: value(4), y: value(5) },
+    ])
+  }
+
+  /**
+   * t(0..=1)での位置(ローカル座標)
+   */
+  pub fn point(&self, t: f64) -> Vec2d {
+    let t = t.clamp(0., 1.);
+    match self.curve {
+      PathCurve::Bezier => {
+        let [p1, p2, p3] = self.points;
+        let s = 1. - t;
+        let (b1, b2, b3) = (3. * s * s * t, 3. * s * t * t, t * t * t);
+        Vec2d {
+          x: b1 * p1.x + b2 * p2.x + b3 * p3.x,
+          y: b1 * p1.y + b2 * p2.y + b3 * p3.y,
+        }
+      },
+      PathCurve::CatmullRom => {
+        let (p0, p1, p2, p3, u) = self.segment(t);
+        let f = |a: f64, b: f64, c: f64, d: f64| {
+          0.5 * (2. * b + (c - a) * u + (2. * a - 5. * b + 4. * c - d) * u * u + (-a + 3. * b - 3. * c + d) * u * u * u)
+        };
+        Vec2d {
+          x: f(p0.x, p1.x, p2.x, p3.x),
+          y: f(p0.y, p1.y, p2.y, p3.y),
+        }
+      },
+    }
+  }
+
+  /**
+   * t(0..=1)での接線方向(ローカル座標、長さは不定)
+   */
+  pub fn tangent(&self, t: f64) -> Vec2d {
+    let t = t.clamp(0., 1.);
+    match self.curve {
+      PathCurve::Bezier => {
+        let p0 = Vec2d { x: 0., y: 0. };
+        let [p1, p2, p3] = self.points;
+        let s = 1. - t;
+        let (b0, b1, b2) = (3. * s * s, 6. * s * t, 3. * t * t);
+        let (d0, d1, d2) = (p1 - p0, p2 - p1, p3 - p2);
+        Vec2d {
+          x: b0 * d0.x + b1 * d1.x + b2 * d2.x,
+          y: b0 * d0.y + b1 * d1.y + b2 * d2.y,
+        }
+      },
+      PathCurve::CatmullRom => {
+        let (p0, p1, p2, p3, u) = self.segment(t);
+        let f = |a: f64, b: f64, c: f64, d: f64| {
+          0.5 * ((c - a) + 2. * (2. * a - 5. * b + 4. * c - d) * u + 3. * (-a + 3. * b - 3. * c + d) * u * u)
+        };
+        Vec2d {
+          x: f(p0.x, p1.x, p2.x, p3.x),
+          y: f(p0.y, p1.y, p2.y, p3.y),
+        }
+      },
+    }
+  }
+
+  /**
+   * Catmull-Romのtが属する区間の4点と区間内のパラメータ
+   * 両端は折り返した仮想点で補う
+   */
+  fn segment(&self, t: f64) -> (Vec2d, Vec2d, Vec2d, Vec2d, f64) {
+    let origin = Vec2d { x: 0., y: 0. };
+    let [p1, p2, p3] = self.points;
+    let knots = [
+      Vec2d { x: 2. * origin.x - p1.x, y: 2. * origin.y - p1.y },
+      origin,
+      p1,
+      p2,
+      p3,
+      Vec2d { x: 2. * p3.x - p2.x, y: 2. * p3.y - p2.y },
+    ];
+    let scaled = t * 3.;
+    let seg = (scaled as usize).min(2);
+    (knots[seg], knots[seg + 1], knots[seg + 2], knots[seg + 3], scaled - seg as f64)
+  }
+
+  /**
+   * ローカル座標 -> 発射点/発射角度を基準にしたワールド座標
+   */
+  pub fn to_world(local: Vec2d, origin_x: f64, origin_y: f64, angle: f64) -> Vec2d {
+    let (sin, cos) = angle.sin_cos();
+    Vec2d {
+      x: origin_x + local.x * cos + local.y * sin,
+      y: origin_y - local.x * sin + local.y * cos,
+    }
+  }
+}
+
+impl Default for Path {
+  fn default() -> Self {
+    Path::new(PathCurve::Bezier, [
+      Vec2d { x: 60., y: 100. },
+      Vec2d { x: -60., y: 200. },
+      Vec2d { x: 0., y: 300. },
+    ])
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn approx(a: Vec2d, b: Vec2d) -> bool {
+    (a.x - b.x).abs() < 1e-9 && (a.y - b.y).abs() < 1e-9
+  }
+
+  #[test]
+  fn test_curves_pass_through_endpoints() {
+    for &curve in &[PathCurve::Bezier, PathCurve::CatmullRom] {
+      let path = Path::from_slice(curve, &[30., 50., -30., 100., 10., 150.]);
+      assert!(approx(path.point(0.), Vec2d { x: 0., y: 0. }));
+      assert!(approx(path.point(1.), Vec2d { x: 10., y: 150. }));
+    }
+    // Catmull-Romは中間点も通る
+    let path = Path::from_slice(PathCurve::CatmullRom, &[30., 50., -30., 100., 10., 150.]);
+    assert!(approx(path.point(1. / 3.), Vec2d { x: 30., y: 50. }));
+    assert!(approx(path.point(2. / 3.), Vec2d { x: -30., y: 100. }));
+  }
+
+  #[test]
+  fn test_exit_tangent() {
+    let path = Path::from_slice(PathCurve::Bezier, &[0., 10., 0., 20., 10., 20.]);
+    let tangent = path.tangent(1.);
+    assert!(tangent.x > 0. && tangent.y.abs() < 1e-9);
+    let path = Path::from_slice(PathCurve::CatmullRom, &[0., 10., 0., 20., 10., 20.]);
+    assert!(approx(path.tangent(1.), Vec2d { x: 10., y: 0. }));
+  }
+
+  #[test]
+  fn test_to_world_follows_shot_angle() {
+    let forward = Vec2d { x: 0., y: 10. };
+    // 角度0は+y方向(下)
+    assert!(approx(Path::to_world(forward, 5., 5., 0.), Vec2d { x: 5., y: 15. }));
+    // 90°回転で+x方向
+    assert!(approx(Path::to_world(forward, 5., 5., std::f64::consts::FRAC_PI_2), Vec2d { x: 15., y: 5. }));
+  }
+}
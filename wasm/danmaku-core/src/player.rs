@@ -0,0 +1,122 @@
+use serde::{ Serialize };
+
+/**
+ * 自機の入力状態
+ */
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct PlayerInput {
+  pub up: bool,
+  pub down: bool,
+  pub left: bool,
+  pub right: bool,
+  pub focus: bool, // 低速移動
+}
+
+/**
+ * 自機に関して1フレーム内で発生したイベント
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind")]
+pub enum PlayerEvent {
+  Hit { x: f64, y: f64 }, // 被弾(弾の座標、レーザー/曲がるレーザーは自機に最も近い中心線上の点)
+  Graze { x: f64, y: f64 }, // グレイズ(被弾と同様)
+}
+
+/**
+ * 自機
+ */
+#[derive(Debug, Clone)]
+pub struct Player {
+  pub x: f64,
+  pub y: f64,
+  pub speed: f64,       // 通常移動速度
+  pub focus_speed: f64, // 低速移動速度
+  pub hit_radius: f64,  // 当たり判定半径
+  pub graze_radius: f64, // グレイズ判定半径
+  pub input: PlayerInput,
+}
+
+impl Player {
+  pub fn new(x: f64, y: f64, speed: f64, focus_speed: f64, hit_radius: f64, graze_radius: f64) -> Self {
+    Player {
+      x,
+      y,
+      speed,
+      focus_speed,
+      hit_radius,
+      graze_radius,
+      input: PlayerInput::default(),
+    }
+  }
+
+  pub fn set_input(&mut self, input: PlayerInput) {
+    self.input = input;
+  }
+
+  /**
+   * 入力に従って移動(画面外には出ない)
+   */
+  pub fn update(&mut self, width: f64, height: f64) {
+    let speed = if self.input.focus { self.focus_speed } else { self.speed };
+    let dx = (self.input.right as i32 - self.input.left as i32) as f64;
+    let dy = (self.input.down as i32 - self.input.up as i32) as f64;
+    // 斜め移動時も速度を揃える
+    let norm = if dx != 0. && dy != 0. { std::f64::consts::FRAC_1_SQRT_2 } else { 1. };
+    self.x = (self.x + dx * speed * norm).max(0.).min(width);
+    self.y = (self.y + dy * speed * norm).max(0.).min(height);
+  }
+
+  /**
+   * 弾(中心x, y/半径radius)との当たり判定(円同士)
+   */
+  pub fn hit_test(&self, x: f64, y: f64, radius: f64) -> bool {
+    self.overlaps(x, y, radius + self.hit_radius)
+  }
+
+  /**
+   * 弾(中心x, y/半径radius)とのグレイズ判定
+   */
+  pub fn graze_test(&self, x: f64, y: f64, radius: f64) -> bool {
+    self.overlaps(x, y, radius + self.graze_radius)
+  }
+
+  fn overlaps(&self, x: f64, y: f64, r: f64) -> bool {
+    let dx = x - self.x;
+    let dy = y - self.y;
+    dx * dx + dy * dy < r * r
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_update_moves_and_clamps() {
+    let mut player = Player::new(10., 10., 4., 2., 3., 20.);
+    player.set_input(PlayerInput { left: true, ..PlayerInput::default() });
+    player.update(100., 100.);
+    assert_eq!((player.x, player.y), (6., 10.));
+    player.set_input(PlayerInput { left: true, focus: true, ..PlayerInput::default() });
+    player.update(100., 100.);
+    assert_eq!((player.x, player.y), (4., 10.));
+    player.set_input(PlayerInput { left: true, ..PlayerInput::default() });
+    player.update(100., 100.);
+    player.update(100., 100.);
+    assert_eq!(player.x, 0.);
+  }
+
+  #[test]
+  fn test_hit_test() {
+    let player = Player::new(50., 50., 4., 2., 3., 20.);
+    assert!(player.hit_test(50., 54., 2.));
+    assert!(!player.hit_test(50., 56., 2.));
+  }
+
+  #[test]
+  fn test_graze_test() {
+    let player = Player::new(50., 50., 4., 2., 3., 20.);
+    assert!(player.graze_test(50., 70., 2.));
+    assert!(!player.graze_test(50., 73., 2.));
+  }
+}
@@ -0,0 +1,596 @@
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use super::disk::{ Disk, DiskColor, DiskType };
+use super::shot::{ Children, ShotBehavior };
+use super::timeline::{ Timeline };
+use super::vec2d::{ Vec2d };
+
+pub const FLAG_ALIVE: u8 = 1;
+pub const FLAG_GRAZED: u8 = 1 << 1; // グレイズ済み(1弾につき1回のみカウント)
+pub const FLAG_TRAIL: u8 = 1 << 2;  // 軌跡を持つ(曲がるレーザー)
+pub const FLAG_HIT: u8 = 1 << 3;    // 自機に当たり済み(曲がるレーザーは当たっても消えない)
+
+/**
+ * Diskへの安定した参照
+ * スロットが再利用されるとgenerationが変わるので、消滅済みのDiskを指すことはない
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct DiskHandle {
+  pub index: u32,
+  pub generation: u32,
+}
+
+/**
+ * 生存しているDiskの読み取り用スナップショット
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DiskView {
+  pub handle: DiskHandle,
+  pub x: f64,
+  pub y: f64,
+  pub vec2d: Vec2d,
+  pub speed: f64,
+  pub angle: f64,
+  pub age: u32,
+  pub disk_type: DiskType,
+  pub disk_size: f64,
+  pub disk_color: DiskColor,
+  pub grazed: bool,
+  pub has_trail: bool, // 曲がるレーザー(胴体はDiskPool::trailで引く)
+}
+
+/**
+ * プールが満杯の時に新しいDiskをどう扱うか
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum OverflowPolicy {
+  DropNewest,          // 新しいDiskを破棄
+  EvictOldest,         // 最も古いDiskを消して空ける
+  EvictLowestPriority, // 優先度の低いThreadのDiskから消して空ける(同じ優先度なら古い順)
+}
+
+pub fn resolve_overflow_policy(num: u32) -> OverflowPolicy {
+  match num {
+    1 => OverflowPolicy::EvictOldest,
+    2 => OverflowPolicy::EvictLowestPriority,
+    _ => OverflowPolicy::DropNewest,
+  }
+}
+
+/**
+ * 同じ発射イベントで生成されたDisk群が共有する挙動
+ */
+#[derive(Debug, Clone)]
+pub struct SpawnGroup {
+  pub behavior: Vec<ShotBehavior>,
+  pub priority: i32, // 発射したThreadの優先度
+  pub children: Children, // 分裂弾/発射台弾の子弾設定
+  pub timeline: Timeline, // ageに応じた挙動の段階切り替え
+  pub emitter: Option<(u32, Vec2d)>, // 発射元のThread idと、その現在の発射点(回転弾が追従する場合のみ)
+  count: u32, // このグループで生存しているDisk数
+}
+
+/**
+ * Disk群を追加する時の設定(SpawnGroupの生成元)
+ */
+#[derive(Debug, Clone, Default)]
+pub struct SpawnSpec {
+  pub behavior: Vec<ShotBehavior>,
+  pub priority: i32, // 発射したThreadの優先度
+  pub children: Children, // 分裂弾/発射台弾の子弾設定
+  pub timeline: Timeline, // ageに応じた挙動の段階切り替え
+  pub emitter: Option<(u32, Vec2d)>, // 発射元のThread idと、発射時の発射点
+}
+
+impl SpawnSpec {
+  pub fn new(behavior: Vec<ShotBehavior>) -> Self {
+    SpawnSpec { behavior, ..SpawnSpec::default() }
+  }
+
+  pub fn with_priority(mut self, priority: i32) -> Self {
+    self.priority = priority;
+    self
+  }
+
+  pub fn with_children(mut self, children: Children) -> Self {
+    self.children = children;
+    self
+  }
+
+  pub fn with_timeline(mut self, timeline: Timeline) -> Self {
+    self.timeline = timeline;
+    self
+  }
+
+  pub fn with_emitter(mut self, thread_id: u32, origin: Vec2d) -> Self {
+    self.emitter = Some((thread_id, origin));
+    self
+  }
+}
+
+/**
+ * Diskプール
+ * Diskの状態は列毎の配列(SoA)で持ち、毎フレームの更新でアロケーションしない
+ * 空きスロットをフリーリストで管理するので、生成/消滅はどちらもO(1)
+ * 満杯時はmax_capacityまで拡張し、それでも足りない分はOverflowPolicyに従う
+ */
+#[derive(Debug, Clone)]
+pub struct DiskPool {
+  pub(crate) x: Vec<f64>,
+  pub(crate) y: Vec<f64>,
+  pub(crate) vx: Vec<f64>,
+  pub(crate) vy: Vec<f64>,
+  pub(crate) speed: Vec<f64>,
+  pub(crate) angle: Vec<f64>,
+  pub(crate) origin_x: Vec<f64>,     // 発射時の座標/角度(パス弾の基準)
+  pub(crate) origin_y: Vec<f64>,
+  pub(crate) origin_angle: Vec<f64>,
+  pub(crate) phase: Vec<f64>,        // 揺れ弾の初期位相
+  pub(crate) emit_count: Vec<u32>,   // 発射台弾の発射済み回数
+  pub(crate) orbit_ticks: Vec<u32>,  // 回転弾が回ったtick数(停止中は進まない)
+  pub(crate) trails: Vec<VecDeque<Vec2d>>, // 曲がるレーザーの軌跡(末尾が先頭の現在位置)
+  pub(crate) age: Vec<u32>,
+  pub(crate) reflect_count: Vec<Option<u32>>,
+  pub(crate) wrap_count: Vec<Option<u32>>, // 画面端ループの残り回数(Noneで無制限)
+  pub(crate) disk_size: Vec<f64>,
+  pub(crate) disk_type: Vec<DiskType>,
+  pub(crate) disk_color: Vec<DiskColor>,
+  pub(crate) flags: Vec<u8>,
+  pub(crate) group: Vec<u32>,
+  pub(crate) groups: Vec<SpawnGroup>,
+  generations: Vec<u32>,
+  free: Vec<usize>,       // 空きスロット(末尾から使う)
+  free_groups: Vec<usize>, // 空きグループ
+  max_capacity: usize,     // 拡張時の上限
+  policy: OverflowPolicy,
+  dropped: u32, // 満杯で破棄した累計Disk数
+  evicted: u32, // 空きを作るために消した累計Disk数
+  evict_buffer: Vec<(i32, Reverse<u32>, usize)>, // 追い出し候補(満杯の度にアロケーションしないよう使い回す)
+}
+
+impl DiskPool {
+  /**
+   * 拡張なし/満杯時は新しいDiskを破棄するプール
+   */
+  pub fn new(capacity: usize) -> Self {
+    DiskPool::with_policy(capacity, capacity, OverflowPolicy::DropNewest)
+  }
+
+  pub fn with_policy(capacity: usize, max_capacity: usize, policy: OverflowPolicy) -> Self {
+    DiskPool {
+      x: vec![0.; capacity],
+      y: vec![0.; capacity],
+      vx: vec![0.; capacity],
+      vy: vec![0.; capacity],
+      speed: vec![0.; capacity],
+      angle: vec![0.; capacity],
+      origin_x: vec![0.; capacity],
+      origin_y: vec![0.; capacity],
+      origin_angle: vec![0.; capacity],
+      phase: vec![0.; capacity],
+      emit_count: vec![0; capacity],
+      orbit_ticks: vec![0; capacity],
+      trails: vec![VecDeque::new(); capacity],
+      age: vec![0; capacity],
+      reflect_count: vec![None; capacity],
+      wrap_count: vec![None; capacity],
+      disk_size: vec![0.; capacity],
+      disk_type: vec![DiskType::Oval; capacity],
+      disk_color: vec![DiskColor::Red; capacity],
+      flags: vec![0; capacity],
+      group: vec![0; capacity],
+      groups: vec![],
+      generations: vec![0; capacity],
+      // 先頭のスロットから使われるように逆順で積む
+      free: (0..capacity).rev().collect(),
+      free_groups: vec![],
+      max_capacity: max_capacity.max(capacity),
+      policy,
+      dropped: 0,
+      evicted: 0,
+      evict_buffer: vec![],
+    }
+  }
+
+  pub fn capacity(&self) -> usize {
+    self.flags.len()
+  }
+
+  /**
+   * 生存しているDisk数
+   */
+  pub fn len(&self) -> usize {
+    self.capacity() - self.free.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  pub fn is_full(&self) -> bool {
+    self.free.is_empty()
+  }
+
+  pub fn is_alive(&self, index: usize) -> bool {
+    self.flags[index] & FLAG_ALIVE != 0
+  }
+
+  pub fn overflow_policy(&self) -> OverflowPolicy {
+    self.policy
+  }
+
+  /**
+   * 満杯で破棄した累計Disk数
+   */
+  pub fn dropped_count(&self) -> u32 {
+    self.dropped
+  }
+
+  /**
+   * 空きを作るために消した累計Disk数
+   */
+  pub fn evicted_count(&self) -> u32 {
+    self.evicted
+  }
+
+  /**
+   * specの挙動を共有するDisk群を空きスロットに追加する
+   * 空きが足りない場合は拡張/OverflowPolicyに従って空きを作り、それでも入らない分は破棄
+   * 追加できた数を返す
+   */
+  pub fn spawn_group<I>(&mut self, spec: SpawnSpec, disks: I) -> usize
+  where
+    I: IntoIterator<Item = Disk>,
+    I::IntoIter: ExactSizeIterator,
+  {
+    let disks = disks.into_iter();
+    let requested = disks.len();
+    self.reserve(requested, spec.priority);
+
+    let reflect_count = spec.behavior
+      .iter()
+      .find_map(|&sb| match sb {
+        ShotBehavior::Reflect(num) => num,
+        _ => None,
+      });
+    let wrap_count = spec.behavior
+      .iter()
+      .find_map(|&sb| match sb {
+        ShotBehavior::Wrap(_, num) => num,
+        _ => None,
+      });
+    let flags = if spec.behavior.iter().any(|sb| matches!(sb, ShotBehavior::CurvyLaser(_))) {
+      FLAG_ALIVE | FLAG_TRAIL
+    } else {
+      FLAG_ALIVE
+    };
+    let group = self.add_group(spec);
+    let mut spawned = 0;
+    for disk in disks {
+      let index = match self.free.pop() {
+        Some(index) => index,
+        None => break,
+      };
+      let vec2d = Vec2d::new(disk.angle, disk.speed);
+      self.x[index] = disk.x;
+      self.y[index] = disk.y;
+      self.vx[index] = vec2d.x;
+      self.vy[index] = vec2d.y;
+      self.speed[index] = disk.speed;
+      self.angle[index] = disk.angle;
+      self.origin_x[index] = disk.x;
+      self.origin_y[index] = disk.y;
+      self.origin_angle[index] = disk.angle;
+      self.phase[index] = disk.phase;
+      self.emit_count[index] = 0;
+      self.orbit_ticks[index] = 0;
+      // 軌跡は確保済みの領域を使い回す
+      self.trails[index].clear();
+      self.age[index] = 0;
+      self.reflect_count[index] = reflect_count;
+      self.wrap_count[index] = wrap_count;
+      self.disk_size[index] = disk.disk_size;
+      self.disk_type[index] = disk.disk_type;
+      self.disk_color[index] = disk.disk_color;
+      self.flags[index] = flags;
+      self.group[index] = group as u32;
+      spawned += 1;
+    }
+    self.groups[group].count = spawned as u32;
+    if spawned == 0 {
+      self.free_groups.push(group);
+    }
+    self.dropped += (requested - spawned) as u32;
+    spawned
+  }
+
+  /**
+   * requested個分の空きスロットを確保する
+   */
+  fn reserve(&mut self, requested: usize, priority: i32) {
+    if self.free.len() >= requested { return }
+
+    // 上限までは倍々で拡張
+    if self.capacity() < self.max_capacity {
+      let needed = self.len() + requested;
+      let capacity = (self.capacity() * 2).max(needed).min(self.max_capacity);
+      self.grow(capacity);
+    }
+
+    let shortage = requested.saturating_sub(self.free.len());
+    if shortage == 0 { return }
+    match self.policy {
+      OverflowPolicy::DropNewest => (),
+      OverflowPolicy::EvictOldest => self.evict(shortage, None),
+      OverflowPolicy::EvictLowestPriority => self.evict(shortage, Some(priority)),
+    }
+  }
+
+  /**
+   * 生存しているDiskからnum個を消す
+   * priority指定時はその優先度以下のDiskのみを対象に、優先度が低い順 -> 古い順
+   */
+  fn evict(&mut self, num: usize, priority: Option<i32>) {
+    let mut candidates = std::mem::take(&mut self.evict_buffer);
+    candidates.clear();
+    candidates.extend(
+      (0..self.capacity())
+        .filter(|&i| self.is_alive(i))
+        .filter_map(|i| {
+          let disk_priority = self.groups[self.group[i] as usize].priority;
+          match priority {
+            Some(priority) if disk_priority > priority => None,
+            Some(_) => Some((disk_priority, Reverse(self.age[i]), i)),
+            None => Some((0, Reverse(self.age[i]), i)),
+          }
+        })
+    );
+    // 消す分だけを先頭に集める(全体はソートしない)
+    if candidates.len() > num {
+      candidates.select_nth_unstable(num - 1);
+      candidates.truncate(num);
+    }
+    for &(_, _, i) in candidates.iter() {
+      self.despawn(i);
+      self.evicted += 1;
+    }
+    self.evict_buffer = candidates;
+  }
+
+  /**
+   * スロット数をcapacityまで増やす
+   */
+  fn grow(&mut self, capacity: usize) {
+    let current = self.capacity();
+    if capacity <= current { return }
+    self.x.resize(capacity, 0.);
+    self.y.resize(capacity, 0.);
+    self.vx.resize(capacity, 0.);
+    self.vy.resize(capacity, 0.);
+    self.speed.resize(capacity, 0.);
+    self.angle.resize(capacity, 0.);
+    self.origin_x.resize(capacity, 0.);
+    self.origin_y.resize(capacity, 0.);
+    self.origin_angle.resize(capacity, 0.);
+    self.phase.resize(capacity, 0.);
+    self.emit_count.resize(capacity, 0);
+    self.orbit_ticks.resize(capacity, 0);
+    self.trails.resize_with(capacity, VecDeque::new);
+    self.age.resize(capacity, 0);
+    self.reflect_count.resize(capacity, None);
+    self.wrap_count.resize(capacity, None);
+    self.disk_size.resize(capacity, 0.);
+    self.disk_type.resize(capacity, DiskType::Oval);
+    self.disk_color.resize(capacity, DiskColor::Red);
+    self.flags.resize(capacity, 0);
+    self.group.resize(capacity, 0);
+    self.generations.resize(capacity, 0);
+    // 既存の空きスロットを先に使うように、追加分はスタックの底に積む
+    let mut free = (current..capacity).rev().collect::<Vec<_>>();
+    free.append(&mut self.free);
+    self.free = free;
+  }
+
+  fn add_group(&mut self, spec: SpawnSpec) -> usize {
+    let SpawnSpec { behavior, priority, children, timeline, emitter } = spec;
+    match self.free_groups.pop() {
+      Some(group) => {
+        self.groups[group].behavior = behavior;
+        self.groups[group].timeline = timeline;
+        self.groups[group].priority = priority;
+        self.groups[group].children = children;
+        self.groups[group].emitter = emitter;
+        group
+      },
+      None => {
+        self.groups.push(SpawnGroup { behavior, priority, children, timeline, emitter, count: 0 });
+        self.groups.len() - 1
+      },
+    }
+  }
+
+  /**
+   * スロットのDiskを消滅させる
+   */
+  pub fn despawn(&mut self, index: usize) -> bool {
+    if !self.is_alive(index) { return false }
+    self.flags[index] = 0;
+    self.generations[index] = self.generations[index].wrapping_add(1);
+    self.free.push(index);
+
+    let group = self.group[index] as usize;
+    self.groups[group].count -= 1;
+    if self.groups[group].count == 0 {
+      self.free_groups.push(group);
+    }
+    true
+  }
+
+  /**
+   * スロットのDiskに適用する挙動
+   */
+  pub fn behavior(&self, index: usize) -> &[ShotBehavior] {
+    &self.groups[self.group[index] as usize].behavior
+  }
+
+  pub fn handle_of(&self, index: usize) -> DiskHandle {
+    DiskHandle {
+      index: index as u32,
+      generation: self.generations[index],
+    }
+  }
+
+  /**
+   * スロット番号で参照(グリッド検索結果などから引く用)
+   */
+  pub fn view(&self, index: usize) -> Option<DiskView> {
+    if !self.is_alive(index) { return None }
+    Some(DiskView {
+      handle: self.handle_of(index),
+      x: self.x[index],
+      y: self.y[index],
+      vec2d: Vec2d { x: self.vx[index], y: self.vy[index] },
+      speed: self.speed[index],
+      angle: self.angle[index],
+      age: self.age[index],
+      disk_type: self.disk_type[index],
+      disk_size: self.disk_size[index],
+      disk_color: self.disk_color[index],
+      grazed: self.flags[index] & FLAG_GRAZED != 0,
+      has_trail: self.flags[index] & FLAG_TRAIL != 0,
+    })
+  }
+
+  /**
+   * 曲がるレーザーの軌跡(古い順、最後が先頭の現在位置)
+   */
+  pub fn trail(&self, handle: DiskHandle) -> impl Iterator<Item = Vec2d> + '_ {
+    let index = handle.index as usize;
+    let alive = self.generations.get(index) == Some(&handle.generation) && self.is_alive(index);
+    self.trails
+      .get(index)
+      .filter(|_| alive)
+      .into_iter()
+      .flat_map(|trail| trail.iter().copied())
+  }
+
+  pub fn get(&self, handle: DiskHandle) -> Option<DiskView> {
+    let index = handle.index as usize;
+    if self.generations.get(index)? != &handle.generation { return None }
+    self.view(index)
+  }
+
+  /**
+   * 生存しているDisk一覧
+   */
+  pub fn iter(&self) -> impl Iterator<Item = DiskView> + '_ {
+    (0..self.capacity()).filter_map(move |i| self.view(i))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn disk() -> Disk {
+    Disk::new(0., 0., DiskType::Oval, 4., 0., 1., DiskColor::Red)
+  }
+
+  fn normal() -> SpawnSpec {
+    SpawnSpec::new(vec![ShotBehavior::Normal])
+  }
+
+  #[test]
+  fn test_spawn_and_despawn() {
+    let mut pool = DiskPool::new(2);
+    assert_eq!(pool.spawn_group(normal(), vec![disk(), disk(), disk()]), 2);
+    assert!(pool.is_full());
+    let handles = pool.iter().map(|d| d.handle).collect::<Vec<_>>();
+    assert_eq!((handles[0].index, handles[1].index), (0, 1));
+
+    pool.despawn(0);
+    assert_eq!(pool.len(), 1);
+    pool.spawn_group(normal(), vec![disk()]);
+    let reused = pool.handle_of(0);
+    assert_ne!(reused.generation, handles[0].generation);
+  }
+
+  #[test]
+  fn test_stale_handle() {
+    let mut pool = DiskPool::new(4);
+    pool.spawn_group(normal(), vec![disk()]);
+    let a = pool.handle_of(0);
+    assert!(pool.get(a).is_some());
+    pool.despawn(0);
+    assert!(pool.get(a).is_none());
+    pool.spawn_group(normal(), vec![disk()]);
+    assert!(pool.get(a).is_none());
+    assert!(pool.despawn(0));
+    assert!(!pool.despawn(0));
+  }
+
+  #[test]
+  fn test_groups_are_shared_and_recycled() {
+    let mut pool = DiskPool::new(8);
+    pool.spawn_group(SpawnSpec::new(vec![ShotBehavior::Reflect(Some(2))]), vec![disk(), disk()]);
+    assert_eq!(pool.groups.len(), 1);
+    assert_eq!(pool.reflect_count[1], Some(2));
+    assert!(matches!(pool.behavior(1), [ShotBehavior::Reflect(Some(2))]));
+
+    // 全弾消滅でグループは再利用される
+    pool.despawn(0);
+    pool.despawn(1);
+    pool.spawn_group(normal(), vec![disk()]);
+    assert_eq!(pool.groups.len(), 1);
+    assert!(matches!(pool.behavior(1), [ShotBehavior::Normal]));
+  }
+
+  #[test]
+  fn test_drop_newest_counts_dropped() {
+    let mut pool = DiskPool::new(4);
+    assert_eq!(pool.spawn_group(normal(), vec![disk(); 6]), 4);
+    assert_eq!(pool.dropped_count(), 2);
+    assert_eq!(pool.evicted_count(), 0);
+  }
+
+  #[test]
+  fn test_grow_up_to_max_capacity() {
+    let mut pool = DiskPool::with_policy(2, 5, OverflowPolicy::DropNewest);
+    pool.spawn_group(normal(), vec![disk(); 2]);
+    let handle = pool.handle_of(0);
+    assert_eq!(pool.spawn_group(normal(), vec![disk()]), 1);
+    assert_eq!(pool.capacity(), 4);
+    assert!(pool.get(handle).is_some());
+    assert_eq!(pool.spawn_group(normal(), vec![disk(); 3]), 2);
+    assert_eq!(pool.capacity(), 5);
+    assert_eq!(pool.dropped_count(), 1);
+  }
+
+  #[test]
+  fn test_evict_oldest() {
+    let mut pool = DiskPool::with_policy(3, 3, OverflowPolicy::EvictOldest);
+    pool.spawn_group(normal(), vec![disk(); 3]);
+    pool.age[0] = 2;
+    pool.age[1] = 5;
+    pool.age[2] = 1;
+    assert_eq!(pool.spawn_group(normal(), vec![disk(); 2]), 2);
+    assert_eq!(pool.evicted_count(), 2);
+    assert_eq!(pool.dropped_count(), 0);
+    // 最も新しい弾(age 1)は残る
+    assert!(pool.iter().any(|d| d.age == 1));
+  }
+
+  #[test]
+  fn test_evict_lowest_priority() {
+    let mut pool = DiskPool::with_policy(4, 4, OverflowPolicy::EvictLowestPriority);
+    pool.spawn_group(normal().with_priority(1), vec![disk(); 2]);
+    pool.spawn_group(normal().with_priority(5), vec![disk(); 2]);
+    // 優先度1の弾だけが消される
+    assert_eq!(pool.spawn_group(normal().with_priority(3), vec![disk(); 3]), 2);
+    assert_eq!(pool.evicted_count(), 2);
+    assert_eq!(pool.dropped_count(), 1);
+    let mut priorities = (0..4).map(|i| pool.groups[pool.group[i] as usize].priority).collect::<Vec<_>>();
+    priorities.sort_unstable();
+    assert_eq!(priorities, vec![3, 3, 5, 5]);
+  }
+}
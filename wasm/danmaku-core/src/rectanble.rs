@@ -0,0 +1,56 @@
+#[derive(Debug)]
+pub struct Rectangle {
+  pub x: f64,
+  pub y: f64,
+  pub width: f64,
+  pub height: f64,
+}
+
+impl Rectangle {
+  pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+    Self { x, y, width, height }
+  }
+
+  pub fn hit_test(&self, other: &Self) -> bool {
+    let horizontal = (other.x < self.x + self.width) && (self.x < other.x + other.width);
+    let vertical = (other.y < self.y + self.height) && (self.y < other.y + other.height);
+    horizontal && vertical
+  }
+
+  /**
+   * 円(中心x, y/半径radius)との当たり判定
+   */
+  pub fn overlaps_circle(&self, x: f64, y: f64, radius: f64) -> bool {
+    let nearest_x = x.max(self.x).min(self.x + self.width);
+    let nearest_y = y.max(self.y).min(self.y + self.height);
+    let dx = x - nearest_x;
+    let dy = y - nearest_y;
+    dx * dx + dy * dy < radius * radius
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn hit_test_test() {
+    let r1 = Rectangle::new(10., 10., 10., 10.);
+    let r2 = Rectangle::new(15., 15., 10., 10.);
+    let r3 = Rectangle::new(20., 10., 10., 10.);
+    let r4 = Rectangle::new(40., 40., 10., 10.);
+    assert!(r1.hit_test(&r2));
+    assert!(r2.hit_test(&r1));
+    assert!(!r1.hit_test(&r3));
+    assert!(!r1.hit_test(&r4));
+  }
+
+  #[test]
+  fn overlaps_circle_test() {
+    let r = Rectangle::new(10., 10., 10., 10.);
+    assert!(r.overlaps_circle(15., 15., 1.));
+    assert!(r.overlaps_circle(8., 15., 3.));
+    assert!(!r.overlaps_circle(5., 5., 5.));
+    assert!(r.overlaps_circle(7., 7., 5.));
+  }
+}
@@ -0,0 +1,75 @@
+/**
+ * シード指定可能な疑似乱数生成器(SplitMix64)
+ * 外部クレート/プラットフォームに依存しないので、同じシードなら同じ乱数列になる
+ */
+#[derive(Debug, Clone)]
+pub struct Rng {
+  seed: u32,
+  state: u64,
+}
+
+impl Rng {
+  pub fn new(seed: u32) -> Self {
+    Rng {
+      seed,
+      state: seed as u64,
+    }
+  }
+
+  /**
+   * シード未指定時はランダムなシードで初期化する
+   */
+  pub fn from_seed_or_random(seed: Option<u32>) -> Self {
+    Rng::new(seed.unwrap_or_else(rand::random))
+  }
+
+  pub fn seed(&self) -> u32 {
+    self.seed
+  }
+
+  pub fn next_u64(&mut self) -> u64 {
+    self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = self.state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+  }
+
+  /**
+   * [0, 1)の一様乱数
+   */
+  pub fn next_f64(&mut self) -> f64 {
+    (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+  }
+
+  /**
+   * [low, high)の一様乱数
+   */
+  pub fn gen_range(&mut self, low: f64, high: f64) -> f64 {
+    low + (high - low) * self.next_f64()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_same_seed_same_sequence() {
+    let mut a = Rng::new(42);
+    let mut b = Rng::new(42);
+    for _ in 0..100 {
+      assert_eq!(a.next_u64(), b.next_u64());
+    }
+    assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+  }
+
+  #[test]
+  fn test_gen_range() {
+    let mut rng = Rng::new(7);
+    for _ in 0..1000 {
+      let v = rng.gen_range(-2., 3.);
+      assert!((-2. ..3.).contains(&v));
+    }
+  }
+}
@@ -1,20 +1,24 @@
 use super::event::{ Event };
 use super::event_thread::{ EventThread };
-use super::Disk;
+use super::laser::{ Laser };
+use super::pool::{ DiskPool };
+use super::rng::{ Rng };
 
 #[derive(Debug)]
 pub struct Schedule {
   gen_id: u32,
-  end_at: u32,
+  tick_rate: u32, // 1秒あたりのtick数
+  end_at: u32, // 1イテレーションのtick数
   iter: u32,
   events: Vec<Event>,
   pub threads: Vec<EventThread>,
 }
 
 impl Schedule {
-  pub fn new() -> Self {
+  pub fn new(tick_rate: u32) -> Self {
     Schedule {
       gen_id: 0,
+      tick_rate,
       end_at: 0,
       iter: 0,
       events: vec![],
@@ -26,6 +30,13 @@ impl Schedule {
     self.iter += 1;
   }
 
+  /**
+   * スケジュール上の現在時刻(tick)
+   */
+  pub fn iteration(&self) -> u32 {
+    self.iter
+  }
+
   pub fn reset_iteration(&mut self) {
     self.iter = 0;
   }
@@ -35,8 +46,15 @@ impl Schedule {
     self.gen_id
   }
 
-  pub fn set_end_at(&mut self, end_at: u32) {
-    self.end_at = end_at; 
+  /**
+   * 1イテレーションの長さ(ms)をtick数に変換して設定する
+   */
+  pub fn set_end_at(&mut self, iteration_ms: u32) {
+    self.end_at = self.ms_to_tick(iteration_ms);
+  }
+
+  fn ms_to_tick(&self, ms: u32) -> u32 {
+    super::convert_interval_to_frame(ms, self.tick_rate).round() as u32
   }
 
   // threadを追加
@@ -47,7 +65,7 @@ impl Schedule {
     match found {
       Some(nth) => {
         let update_target = self.threads.get_mut(nth)?;
-        update_target.setting = thread.setting;
+        update_target.update_setting(thread.setting);
       },
       None => {
         self.threads.push(thread);
@@ -61,16 +79,15 @@ impl Schedule {
     self.events = self.threads
       .iter()
       .flat_map(|thread| {
-        let start_at_ms = thread.setting.start_at;
-        let end_at_ms = thread.setting.end_at;
+        // 開始/終了/発射間隔(ms)はtick数に変換して比較する
+        let start_at_frame = self.ms_to_tick(thread.setting.start_at);
+        let end_at_frame = self.ms_to_tick(thread.setting.end_at);
         let mut buff: Vec<Event> = vec![];
-        let shot_interval_frame = super::convert_interval_to_frame(thread.setting.shot_interval);
+        let shot_interval_frame = super::convert_interval_to_frame(thread.setting.shot_interval, self.tick_rate);
 
-        // relative_ms -> イテレーション内での相対的ms
-        // absolute_ms -> スクリーン全体での絶対的ms
-        for absolute_ms in start_at_ms..=end_at_ms {
-          if (absolute_ms as f64) % shot_interval_frame == 0. {
-            buff.push(Event::new(thread.id, absolute_ms, absolute_ms)); // TODO: end_at入らない気がするが
+        for frame in start_at_frame..=end_at_frame {
+          if (frame as f64) % shot_interval_frame == 0. {
+            buff.push(Event::new(thread.id, frame, frame)); // TODO: end_at入らない気がするが
           }
         }
         buff
@@ -84,9 +101,10 @@ impl Schedule {
     // self.end_at = end_at.unwrap().end_at;
   }
 
-  // 現イテレーションでのeventを巡回
-  pub fn walkthrough_events(&mut self, disks: &mut Vec<Option<Disk>>) -> Option<()> {
+  // 現イテレーションでのeventを巡回(playerは自機狙いの目標)
+  pub fn walkthrough_events(&mut self, disks: &mut DiskPool, lasers: &mut Vec<Laser>, rng: &mut Rng, player: (f64, f64)) -> Option<()> {
     let iter = self.iter;
+    let tick_rate = self.tick_rate;
     // リセット 必要かは検討
     if iter == self.end_at {
       self.reset_iteration();
@@ -99,7 +117,7 @@ impl Schedule {
       let thread = self.threads
         .iter_mut()
         .find(|thread| thread.id == event.thread_id);
-      thread?.spawn_disks(&mut disks.as_mut());
+      thread?.spawn_disks(disks, lasers, rng, tick_rate, iter, player);
     }
     Some(())
   }
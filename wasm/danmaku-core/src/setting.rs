@@ -0,0 +1,619 @@
+use std::sync::Arc;
+use serde::{ Deserialize, Serialize };
+use crate::disk::{resolve_disk_color, DiskColor};
+
+use super::shot::{
+  HomingTarget,
+  ShotType,
+  ShotBehavior, 
+  ShotPattern,
+  ChildSpec,
+  Children,
+  resolve_shot_type,
+  resolve_shot_behavior,
+  WRAP_ALL,
+};
+use super::disk::{ resolve_disk_type, DiskType };
+use super::laser::{ LaserSpec };
+use super::path::{ resolve_path_curve, Path };
+use super::motion::{ resolve_easing, EmitterMotion };
+use super::timeline::{ Stage, Timeline };
+use super::vec2d::{ Vec2d };
+
+pub const MAX_CHILD_DEPTH: usize = 3; // 子弾設定(分裂弾/発射台弾)を辿る最大の深さ
+pub const MAX_EMIT_SHOTS: u32 = 64; // 発射台弾1つあたりの最大発射回数
+pub const MAX_STACK_LAYERS: u32 = 32; // 1回の発射で重ねる並びの最大数
+
+/**
+ * Jsからの入力値
+ * 子弾設定(split, emit)は弾に関する項目だけを渡すので、省略した項目はDefaultの値になる
+ */
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettingOptions {
+  // Screen設定
+  pub canvas_id: String,
+  pub theme: u32,
+  pub width: f64,
+  pub height: f64,
+  pub iteration_ms: u32, // 1イテレーションのトータルms
+  pub start_at: u32, // 開始ms
+  pub end_at: u32,
+  pub seed: Option<u32>, // 乱数シード(未指定時はランダム)
+  pub tick_rate: Option<u32>, // 1秒あたりのtick数
+  pub max_catch_up_ticks: Option<u32>, // 1描画フレームで進める最大tick数
+  pub disk_capacity: Option<u32>, // Diskプールの初期スロット数
+  pub disk_capacity_max: Option<u32>, // Diskプールの拡張上限(未指定時は拡張しない)
+  pub overflow_policy: Option<u32>, // プール満杯時の挙動
+  pub player_speed: Option<f64>, // 自機速度
+  pub player_focus_speed: Option<f64>, // 自機低速移動時の速度
+  pub player_hit_radius: Option<f64>, // 自機当たり判定半径
+  pub player_graze_radius: Option<f64>, // 自機グレイズ判定半径
+
+  // Thread設定
+  pub disk_size: Option<f64>,
+  pub shot_type: Option<u32>,     // 種別
+  pub disk_type: Option<u32>,     // 弾種別
+  pub disk_color: Option<u32>,     // 弾種別
+  pub shot_speed: Option<f64>,    // 速度
+  pub shot_way_num: Option<u32>,  // 発射WAY数
+  pub shot_interval: Option<u32>, // 発射間隔
+  pub shot_behavior: Option<Vec<u32>>, // 弾の挙動
+  pub speed_change_per: Option<u32>, // ショット速度変化率
+  pub speed_change_interval: Option<f64>, // ショット速度変化インターバル
+  pub x_coordinate: Option<f64>, // X座標
+  pub y_coordinate: Option<f64>, // Y座標
+  pub reflect_count: Option<u32>, // 反射数 
+  pub sleep_interval: Option<f64>, // スリープ弾
+  pub sleep_timeout: Option<f64>, // スリープ弾
+  pub degree_change_by: Option<f64>, // 角度変化量
+  pub gravity_direction: Option<u32>, // 重力方向
+  pub gravity_change_per: Option<u32>, // 重力減衰%
+  pub thread_seed: Option<u32>, // Thread固有の乱数シード(未指定時はScreenの乱数を共有)
+  pub homing_turn_rate: Option<f64>, // 誘導弾の1tickあたりの最大旋回角(度)
+  pub homing_delay: Option<u32>, // 誘導開始までのtick数
+  pub homing_duration: Option<u32>, // 誘導するtick数(0で無制限)
+  pub homing_x: Option<f64>, // 誘導先X座標(未指定時は自機)
+  pub homing_y: Option<f64>, // 誘導先Y座標(未指定時は自機)
+  pub path_curve: Option<u32>, // パス弾の曲線種別(0: ベジェ, 1: Catmull-Rom)
+  pub path_points: Option<Vec<f64>>, // パス弾の軌道[x1, y1, x2, y2, x3, y3](発射方向が+y)
+  pub path_duration: Option<u32>, // パス弾が軌道を辿るtick数
+  pub wave_amplitude: Option<f64>, // 揺れ弾の振幅(px)
+  pub wave_frequency: Option<f64>, // 揺れ弾の周波数(Hz)
+  pub wave_phase: Option<f64>, // 揺れ弾の初期位相(度)
+  pub wave_phase_step: Option<f64>, // 揺れ弾のWAY毎の位相ずれ(度)
+  pub split_after: Option<u32>, // 分裂弾が分裂するまでのtick数(0で時間では分裂しない)
+  pub split_on_wall: Option<bool>, // 分裂弾が壁に当たったら分裂するか
+  pub split: Option<Box<SettingOptions>>, // 分裂弾の子弾設定(Thread設定のうち弾に関するもののみ使う、画面サイズは親の設定を使う、レーザーは円形になる)
+  pub emit_interval: Option<u32>, // 発射台弾の発射間隔(tick)
+  pub emit_max_shots: Option<u32>, // 発射台弾の最大発射回数(上限MAX_EMIT_SHOTS)
+  pub emit: Option<Box<SettingOptions>>, // 発射台弾の子弾設定(splitと同様、省略した項目はDefaultの値)
+  pub laser_length: Option<f64>, // レーザーの長さ(未指定時は画面の対角線)
+  pub laser_width: Option<f64>, // レーザーの太さ
+  pub laser_warning: Option<u32>, // 予告線のtick数
+  pub laser_duration: Option<u32>, // 照射するtick数
+  pub laser_rotation: Option<f64>, // 1tickあたりの回転量(度)
+  pub curvy_length: Option<u32>, // 曲がるレーザーの胴体の長さ(先頭の軌跡のtick数)
+  pub orbit_radius: Option<f64>, // 回転弾の初期半径(px)
+  pub orbit_radius_speed: Option<f64>, // 回転弾の1tickあたりの半径変化(px、負数で縮む)
+  pub orbit_angular_speed: Option<f64>, // 回転弾の1tickあたりの回転角(度、負数で反時計回り)
+  pub orbit_release: Option<u32>, // 回転弾が接線方向に離脱するまでのtick数(0で離脱しない)
+  pub orbit_follow: Option<bool>, // 回転弾の中心を発射点の移動に追従させるか(falseでは発射した時点の発射点、子弾では無視)
+  pub emitter_motion: Option<u32>, // 発射点の動き(0: 固定, 1: キーフレーム, 2: 楕円, 3: リサージュ)
+  pub emitter_keyframes: Option<Vec<f64>>, // 発射点のキーフレーム[t1, x1, y1, t2, x2, y2, ...](tはスケジュール上のtick)
+  pub emitter_easing: Option<u32>, // キーフレーム間の補間(0: 線形, 1: ease-in, 2: ease-out, 3: ease-in-out)
+  pub emitter_radius_x: Option<f64>, // 楕円の半径/リサージュの振幅(px)
+  pub emitter_radius_y: Option<f64>,
+  pub emitter_period_x: Option<u32>, // 楕円/リサージュの周期(tick)
+  pub emitter_period_y: Option<u32>, // リサージュのY軸の周期(tick)
+  pub emitter_phase: Option<f64>, // 楕円/リサージュの初期位相(度)
+  pub aim_spread: Option<f64>, // 自機狙いの扇の広がり(度)
+  pub aim_jitter: Option<f64>, // 自機狙いの狙いのブレ(±度)
+  pub aim_burst: Option<u32>, // 何回の発射ごとに狙い直すか(1で毎回)
+  pub aim_x: Option<f64>, // 狙う先のX座標(未指定時は自機)
+  pub aim_y: Option<f64>, // 狙う先のY座標(未指定時は自機)
+  pub rotate_speed: Option<f64>, // 放射状回転/渦巻の1tickあたりの回転角(度)
+  pub swirl_speed: Option<f64>, // 渦巻の弾速(shot_speedの代わりに使う、未指定時は旧版と同じ1)
+  pub linear_spread: Option<f64>, // 扇状の広がり(度)
+  pub linear_center: Option<f64>, // 扇状の中心方向(度、0で真下)
+  pub linear_inclusive: Option<bool>, // 扇状の両端に撃つか
+  pub linear_random: Option<bool>, // 扇状/自機狙いの範囲内にランダムに撃つか
+  pub stack_layers: Option<u32>, // 1回の発射で同じ並びを速度違いで重ねる数(1で重ねない、上限MAX_STACK_LAYERS)
+  pub stack_speed_step: Option<f64>, // 重ねる毎に加える速度
+  pub wrap_edges: Option<u32>, // 画面端ループする辺(1: 左, 2: 右, 4: 上, 8: 下の和、未指定時は全辺)
+  pub wrap_count: Option<u32>, // 画面端ループの最大回数(0で無制限)
+  pub curve_angular_velocity: Option<f64>, // 曲がる弾の1tickあたりの回転角(度、負数で反時計回り)
+  pub curve_angular_acceleration: Option<f64>, // 曲がる弾の1tickあたりの回転角の変化(度)
+  pub curve_max_angular_velocity: Option<f64>, // 曲がる弾の回転角の上限(度、0で上限なし)
+  pub timeline: Option<Vec<StageOptions>>, // ageに応じた挙動の段階(未指定時はスリープ弾の設定から作る)
+  pub timeline_period: Option<u32>, // 段階を繰り返す周期(tick、0で繰り返さない)
+  pub thread_priority: Option<i32>, // プール満杯時の優先度(低いThreadの弾から消される)
+}
+
+/**
+ * Jsからの入力値(弾の挙動の1段階)
+ */
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StageOptions {
+  pub start: u32, // この段階に入るage(tick)
+  pub shot_behavior: Option<Vec<u32>>, // 段階中に追加する挙動(パラメータはThreadの設定値を使う)
+  pub stop: Option<bool>, // 段階中は移動しない
+  pub speed: Option<f64>, // 段階の開始時に変える速度
+  pub aim: Option<bool>, // 段階の開始時に狙い直す(狙う先はaim_x, aim_y、未指定時は自機)
+}
+
+/**
+ * 各Threadに割り当てる設定値
+ */
+#[derive(Debug, Clone)]
+pub struct Setting {
+  pub iteration_ms: u32,
+  pub start_at: u32,
+  pub end_at: u32,
+  pub disk_size: f64,
+  pub disk_type: DiskType,
+  pub disk_color: DiskColor,
+  pub shot_type: ShotType,     // 種別
+  pub shot_behavior: Vec<ShotBehavior>, // 弾の挙動
+  pub shot_speed: f64,    // 速度
+  pub shot_way_num: u32,  // 発射WAY数
+  pub shot_interval: u32, // 発射間隔
+  pub x_coordinate: f64, // X座標
+  pub y_coordinate: f64, // Y座標
+  pub degree_change_by: f64, // 角度変化量
+  pub priority: i32, // プール満杯時の優先度
+
+  // 設定されていなくてもいいもの
+  pub reflect_count: Option<u32>, // 反射数 
+  pub speed_change_per: Option<u32>, // ショット速度変化率(%)
+  pub speed_change_interval: Option<f64>, // ショット速度変化インターバル
+  pub sleep_interval: f64, // スリープ弾
+  pub sleep_timeout: f64, // スリープ弾
+  pub gravity_direction: Option<u32>, // 重力方向
+  pub gravity_change_per: Option<u32>, // 重力減衰%
+  pub homing_turn_rate: f64, // 誘導弾の最大旋回角(度/tick)
+  pub homing_delay: u32, // 誘導開始までのtick数
+  pub homing_duration: u32, // 誘導するtick数
+  pub homing_target: Option<(f64, f64)>, // 誘導先(未指定時は自機)
+  pub path: Path, // パス弾の軌道
+  pub path_duration: u32, // パス弾が軌道を辿るtick数
+  pub wave_amplitude: f64, // 揺れ弾の振幅(px)
+  pub wave_frequency: f64, // 揺れ弾の周波数(Hz)
+  pub wave_phase: f64, // 揺れ弾の初期位相(度)
+  pub wave_phase_step: f64, // 揺れ弾のWAY毎の位相ずれ(度)
+  pub split_after: u32, // 分裂までのtick数
+  pub split_on_wall: bool, // 壁に当たったら分裂
+  pub split: Option<Box<Setting>>, // 分裂弾の子弾設定
+  pub emit_interval: u32, // 発射台弾の発射間隔(tick)
+  pub emit_max_shots: u32, // 発射台弾の最大発射回数
+  pub emit: Option<Box<Setting>>, // 発射台弾の子弾設定
+  pub laser: LaserSpec, // レーザーの形状/照射時間
+  pub curvy_length: u32, // 曲がるレーザーの胴体の長さ(tick)
+  pub orbit_radius: f64, // 回転弾の初期半径(px)
+  pub orbit_radius_speed: f64, // 回転弾の半径変化(px/tick)
+  pub orbit_angular_speed: f64, // 回転弾の回転角(度/tick)
+  pub orbit_release: u32, // 回転弾が離脱するまでのtick数
+  pub orbit_follow: bool, // 回転弾の中心を発射点の移動に追従させるか
+  pub emitter_motion: EmitterMotion, // 発射点の動き(基準点はx_coordinate, y_coordinate)
+  pub aim_spread: f64, // 自機狙いの扇の広がり(度)
+  pub aim_jitter: f64, // 自機狙いの狙いのブレ(±度)
+  pub aim_burst: u32, // 何回の発射ごとに狙い直すか
+  pub aim_target: HomingTarget, // 狙う先
+  pub rotate_speed: f64, // 放射状回転/渦巻の回転角(度/tick)
+  pub swirl_speed: f64, // 渦巻の弾速
+  pub linear_spread: f64, // 扇状の広がり(度)
+  pub linear_center: f64, // 扇状の中心方向(度)
+  pub linear_inclusive: bool, // 扇状の両端に撃つか
+  pub linear_random: bool, // 扇状/自機狙いの範囲内にランダムに撃つか
+  pub stack_layers: u32, // 同じ並びを速度違いで重ねる数
+  pub stack_speed_step: f64, // 重ねる毎に加える速度
+  pub wrap_edges: u32, // 画面端ループする辺
+  pub wrap_count: Option<u32>, // 画面端ループの最大回数(Noneで無制限)
+  pub curve_angular_velocity: f64, // 曲がる弾の回転角(度/tick)
+  pub curve_angular_acceleration: f64, // 曲がる弾の回転角の変化(度/tick)
+  pub curve_max_angular_velocity: f64, // 曲がる弾の回転角の上限(度/tick)
+  pub timeline: Timeline, // ageに応じた挙動の段階(挙動のパラメータは未設定)
+  pub field_width: f64, // 画面幅(撃ち降ろしの並べる幅)
+  pub thread_seed: Option<u32>, // Thread固有の乱数シード
+}
+
+impl Setting {
+  pub fn new(options: &SettingOptions) -> Self {
+    Setting::with_depth(options, 0, (options.width, options.height))
+  }
+
+  /**
+   * depthは子弾設定の深さ(MAX_CHILD_DEPTHを超える子弾設定は無視)
+   * screenは画面サイズ(子弾設定は画面サイズを持たないので親から引き継ぐ)
+   */
+  fn with_depth(options: &SettingOptions, depth: usize, screen: (f64, f64)) -> Self {
+    let (width, height) = screen;
+    let iteration_ms = options.iteration_ms;
+    let start_at = options.start_at;
+    let end_at = options.end_at;
+    let shot_type = options.shot_type.unwrap_or(0);
+    let shot_type = match resolve_shot_type(shot_type) {
+      // 子弾はDiskしか生成できないので、レーザーは同じ向きに並べる円形にする
+      ShotType::Laser if depth > 0 => ShotType::Circle,
+      shot_type => shot_type,
+    };
+    let shot_behavior = options.shot_behavior.clone().unwrap_or(vec![0]);
+    let shot_behavior = shot_behavior
+      .into_iter()
+      .map(resolve_shot_behavior)
+      .collect::<Vec<ShotBehavior>>();
+    let disk_type = options.disk_type.unwrap_or(0);
+    let disk_type = resolve_disk_type(disk_type);
+    let disk_color = options.disk_color.unwrap_or(1);
+    let disk_color = resolve_disk_color(disk_color);
+    let path_curve = resolve_path_curve(options.path_curve.unwrap_or(0));
+    let path = options.path_points
+      .as_ref()
+      .map_or(Path::default(), |points| Path::from_slice(path_curve, points));
+    let emitter_motion = resolve_emitter_motion(options);
+    Self {
+      iteration_ms,
+      start_at,
+      end_at,
+      shot_type,
+      shot_behavior,
+      disk_type,
+      disk_color,
+      disk_size: options.disk_size.unwrap_or(4.),
+      shot_speed: options.shot_speed.unwrap_or(1.0),
+      shot_way_num: options.shot_way_num.unwrap_or(6), 
+      shot_interval: options.shot_interval.unwrap_or(500), 
+      x_coordinate: options.x_coordinate.unwrap_or(width / 2.),
+      y_coordinate: options.y_coordinate.unwrap_or(height / 2.),
+      speed_change_per: options.speed_change_per,
+      speed_change_interval: options.speed_change_interval,
+      reflect_count: options.reflect_count,
+      sleep_interval: options.sleep_interval.unwrap_or(0.),
+      sleep_timeout: options.sleep_timeout.unwrap_or(0.),
+      degree_change_by: options.degree_change_by.unwrap_or(0.),
+      priority: options.thread_priority.unwrap_or(0),
+      gravity_direction: options.gravity_direction,
+      gravity_change_per: options.gravity_change_per,
+      homing_turn_rate: options.homing_turn_rate.unwrap_or(2.),
+      homing_delay: options.homing_delay.unwrap_or(0),
+      homing_duration: options.homing_duration.unwrap_or(0),
+      homing_target: options.homing_x.zip(options.homing_y),
+      path,
+      path_duration: options.path_duration.unwrap_or(120),
+      wave_amplitude: options.wave_amplitude.unwrap_or(20.),
+      wave_frequency: options.wave_frequency.unwrap_or(1.),
+      wave_phase: options.wave_phase.unwrap_or(0.),
+      wave_phase_step: options.wave_phase_step.unwrap_or(0.),
+      split_after: options.split_after.unwrap_or(0),
+      split_on_wall: options.split_on_wall.unwrap_or(false),
+      split: options.split
+        .as_ref()
+        .filter(|_| depth < MAX_CHILD_DEPTH)
+        .map(|child| Box::new(Setting::with_depth(child, depth + 1, screen))),
+      emit_interval: options.emit_interval.unwrap_or(10),
+      emit_max_shots: options.emit_max_shots.unwrap_or(10).min(MAX_EMIT_SHOTS),
+      emit: options.emit
+        .as_ref()
+        .filter(|_| depth < MAX_CHILD_DEPTH)
+        .map(|child| Box::new(Setting::with_depth(child, depth + 1, screen))),
+      laser: LaserSpec {
+        length: options.laser_length.unwrap_or((width * width + height * height).sqrt()),
+        width: options.laser_width.unwrap_or(8.),
+        warning_ticks: options.laser_warning.unwrap_or(60),
+        active_ticks: options.laser_duration.unwrap_or(60),
+        rotation: options.laser_rotation.unwrap_or(0.).to_radians(),
+        color: disk_color,
+      },
+      curvy_length: options.curvy_length.unwrap_or(30),
+      orbit_radius: options.orbit_radius.unwrap_or(0.),
+      orbit_radius_speed: options.orbit_radius_speed.unwrap_or(1.),
+      orbit_angular_speed: options.orbit_angular_speed.unwrap_or(2.),
+      orbit_release: options.orbit_release.unwrap_or(0),
+      orbit_follow: options.orbit_follow.unwrap_or(false),
+      emitter_motion,
+      aim_spread: options.aim_spread.unwrap_or(30.),
+      aim_jitter: options.aim_jitter.unwrap_or(0.),
+      aim_burst: options.aim_burst.unwrap_or(1).max(1),
+      aim_target: options.aim_x
+        .zip(options.aim_y)
+        .map_or(HomingTarget::Player, |(x, y)| HomingTarget::Point(x, y)),
+      rotate_speed: options.rotate_speed.unwrap_or(1.5),
+      swirl_speed: options.swirl_speed.unwrap_or(1.),
+      linear_spread: options.linear_spread.unwrap_or(100.),
+      linear_center: options.linear_center.unwrap_or(0.),
+      linear_inclusive: options.linear_inclusive.unwrap_or(false),
+      linear_random: options.linear_random.unwrap_or(false),
+      stack_layers: options.stack_layers.unwrap_or(1).clamp(1, MAX_STACK_LAYERS),
+      stack_speed_step: options.stack_speed_step.unwrap_or(0.3),
+      wrap_edges: options.wrap_edges.unwrap_or(WRAP_ALL),
+      wrap_count: options.wrap_count.filter(|&num| num > 0),
+      curve_angular_velocity: options.curve_angular_velocity.unwrap_or(1.),
+      curve_angular_acceleration: options.curve_angular_acceleration.unwrap_or(0.),
+      curve_max_angular_velocity: options.curve_max_angular_velocity.unwrap_or(0.),
+      timeline: resolve_timeline(options),
+      field_width: width,
+      thread_seed: options.thread_seed,
+    }
+  }
+
+  /**
+   * スケジュール上の時刻(tick)での発射点
+   */
+  pub fn origin(&self, time: u32) -> Vec2d {
+    self.emitter_motion.position(self.x_coordinate, self.y_coordinate, time)
+  }
+
+  /**
+   * 1回の発射で生成するDiskの並び
+   */
+  pub fn pattern(&self) -> ShotPattern {
+    ShotPattern {
+      shot_type: self.shot_type.clone(),
+      way_num: self.shot_way_num,
+      speed: match self.shot_type {
+        ShotType::Swirl => self.swirl_speed,
+        _ => self.shot_speed,
+      },
+      disk_type: self.disk_type,
+      disk_size: self.disk_size,
+      disk_color: self.disk_color,
+      phase: self.wave_phase.to_radians(),
+      phase_step: self.wave_phase_step.to_radians(),
+      spread: match self.shot_type {
+        ShotType::Aimed => self.aim_spread.to_radians(),
+        _ => self.linear_spread.to_radians(),
+      },
+      center: self.linear_center.to_radians(),
+      inclusive: self.linear_inclusive,
+      random_fan: self.linear_random,
+      width: self.field_width,
+      layers: self.stack_layers,
+      layer_speed_step: self.stack_speed_step,
+    }
+  }
+
+  /**
+   * 設定値を埋めた弾の挙動
+   * tick_rateは秒単位の設定値(揺れ弾の周波数など)をtick単位に直すのに使う
+   */
+  pub fn behavior(&self, tick_rate: u32) -> Vec<ShotBehavior> {
+    self.shot_behavior
+      .iter()
+      .map(|sb| self.fill_behavior(sb, tick_rate))
+      .collect::<Vec<ShotBehavior>>()
+  }
+
+  /**
+   * 設定値を埋めた挙動の段階切り替え
+   */
+  pub fn timeline(&self, tick_rate: u32) -> Timeline {
+    let stages = self.timeline
+      .stages()
+      .iter()
+      .map(|stage| Stage {
+        behavior: stage.behavior
+          .iter()
+          .map(|sb| self.fill_behavior(sb, tick_rate))
+          .collect(),
+        ..stage.clone()
+      })
+      .collect();
+    Timeline::new(stages, self.timeline.period()).with_delay(self.timeline.delay())
+  }
+
+  fn fill_behavior(&self, sb: &ShotBehavior, tick_rate: u32) -> ShotBehavior {
+    match sb {
+      ShotBehavior::SpeedUp(..) => {
+        ShotBehavior::SpeedUp(
+          self.speed_change_interval.unwrap_or(0.),
+          self.speed_change_per.unwrap_or(1) as f64 / 100.,
+        )
+      },
+      ShotBehavior::SpeedDown(..) => {
+        ShotBehavior::SpeedDown(
+          self.speed_change_interval.unwrap_or(0.),
+          self.speed_change_per.unwrap_or(1) as f64 / 100.,
+        )
+      },
+      ShotBehavior::Reflect(_) => ShotBehavior::Reflect(self.reflect_count),
+      ShotBehavior::Gravity(..) => ShotBehavior::Gravity(
+        self.gravity_direction.unwrap_or(0),
+        self.gravity_change_per.unwrap_or(1) as f64 / 100.,
+      ),
+      ShotBehavior::Homing(..) => ShotBehavior::Homing(
+        self.homing_turn_rate.to_radians(),
+        self.homing_delay,
+        self.homing_duration,
+        self.homing_target
+          .map_or(HomingTarget::Player, |(x, y)| HomingTarget::Point(x, y)),
+      ),
+      ShotBehavior::Path(..) => ShotBehavior::Path(self.path, self.path_duration),
+      ShotBehavior::Wave(..) => ShotBehavior::Wave(
+        self.wave_amplitude,
+        2. * std::f64::consts::PI * self.wave_frequency / tick_rate.max(1) as f64,
+      ),
+      ShotBehavior::Split(..) => ShotBehavior::Split(self.split_after, self.split_on_wall),
+      ShotBehavior::Emit(..) => ShotBehavior::Emit(self.emit_interval, self.emit_max_shots),
+      ShotBehavior::CurvyLaser(..) => ShotBehavior::CurvyLaser(self.curvy_length),
+      ShotBehavior::Wrap(..) => ShotBehavior::Wrap(self.wrap_edges, self.wrap_count),
+      ShotBehavior::Curve(..) => ShotBehavior::Curve(
+        self.curve_angular_velocity.to_radians(),
+        self.curve_angular_acceleration.to_radians(),
+        self.curve_max_angular_velocity.abs().to_radians(),
+      ),
+      ShotBehavior::Orbit(..) => ShotBehavior::Orbit(
+        self.orbit_radius,
+        self.orbit_radius_speed,
+        self.orbit_angular_speed.to_radians(),
+        self.orbit_release,
+        self.orbit_follow,
+      ),
+      _ => ShotBehavior::Normal
+    }
+  }
+
+  /**
+   * このSettingで発射されるDisk群の子弾設定
+   */
+  pub fn children(&self, tick_rate: u32) -> Children {
+    let spec = |child: &Setting| {
+      Arc::new(ChildSpec {
+        pattern: child.pattern(),
+        behavior: child.behavior(tick_rate),
+        timeline: child.timeline(tick_rate),
+        children: child.children(tick_rate),
+      })
+    };
+    Children {
+      split: self.split.as_deref().map(spec),
+      emit: self.emit.as_deref().map(spec),
+    }
+  }
+}
+
+/**
+ * 挙動の段階切り替え
+ * timeline未指定でスリープ弾(5)の場合は「sleep_interval毎にsleep_timeoutの間止まる」段階を作る
+ */
+fn resolve_timeline(options: &SettingOptions) -> Timeline {
+  let period = options.timeline_period.unwrap_or(0);
+  if let Some(stages) = options.timeline.as_ref().filter(|stages| !stages.is_empty()) {
+    let stages = stages
+      .iter()
+      .map(|stage| Stage {
+        start: stage.start,
+        behavior: stage.shot_behavior
+          .iter()
+          .flatten()
+          .map(|&num| resolve_shot_behavior(num))
+          .collect(),
+        stop: stage.stop.unwrap_or(false),
+        speed: stage.speed,
+        aim: stage.aim.filter(|&aim| aim).map(|_| {
+          options.aim_x
+            .zip(options.aim_y)
+            .map_or(HomingTarget::Player, |(x, y)| HomingTarget::Point(x, y))
+        }),
+      })
+      .collect();
+    return Timeline::new(stages, period)
+  }
+
+  let sleep = options.shot_behavior.as_ref().is_some_and(|sb| sb.contains(&5));
+  let interval = options.sleep_interval.unwrap_or(0.) as u32;
+  if !sleep || interval == 0 {
+    return Timeline::default()
+  }
+  // 旧版のスリープ弾と同じく、intervalの倍数のageから(最初の周期は除く)timeoutの間止まる
+  let timeout = (options.sleep_timeout.unwrap_or(0.) as u32).min(interval);
+  Timeline::new(vec![
+    Stage { stop: true, ..Stage::new(0) },
+    Stage::new(timeout),
+  ], interval).with_delay(interval)
+}
+
+fn resolve_emitter_motion(options: &SettingOptions) -> EmitterMotion {
+  let radius_x = options.emitter_radius_x.unwrap_or(100.);
+  let radius_y = options.emitter_radius_y.unwrap_or(radius_x);
+  let period_x = options.emitter_period_x.unwrap_or(240);
+  let period_y = options.emitter_period_y.unwrap_or(period_x);
+  let phase = options.emitter_phase.unwrap_or(0.).to_radians();
+  match options.emitter_motion.unwrap_or(0) {
+    1 => EmitterMotion::waypoints_from_slice(
+      options.emitter_keyframes.as_deref().unwrap_or(&[]),
+      resolve_easing(options.emitter_easing.unwrap_or(0)),
+    ),
+    2 => EmitterMotion::Orbit(radius_x, radius_y, period_x, phase),
+    3 => EmitterMotion::Lissajous(radius_x, radius_y, period_x, period_y, phase),
+    _ => EmitterMotion::Fixed,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use super::super::rng::{ Rng };
+
+  #[test]
+  fn test_split_depth_is_bounded() {
+    // 子弾設定を無限に近い深さで入れ子にしてもMAX_CHILD_DEPTHで打ち切る
+    let mut options = SettingOptions::default();
+    for _ in 0..10 {
+      options = SettingOptions {
+        split: Some(Box::new(options)),
+        ..SettingOptions::default()
+      };
+    }
+    let mut depth = 0;
+    let mut setting = Setting::new(&options);
+    while let Some(child) = setting.split {
+      setting = *child;
+      depth += 1;
+    }
+    assert_eq!(depth, MAX_CHILD_DEPTH);
+  }
+
+  // フロントエンド(gameStore.ts)の初期値と同じ形の入力値
+  const FRONTEND_OPTIONS: &str = r#"{
+    "canvas_id": "canvas", "theme": 1, "disk_num": 400, "width": 800, "height": 800,
+    "disk_size": 10, "shot_type": 1, "shot_speed": 1, "shot_way_num": 10, "shot_interval": 1000,
+    "shot_behavior": [0], "speed_change_per": 10, "speed_change_interval": 100,
+    "x_coordinate": 400, "y_coordinate": 400, "reflect_count": 0,
+    "iteration_ms": 2000, "start_at": 0, "end_at": 2000,
+    "sleep_interval": 50, "sleep_timeout": 20, "degree_change_by": 1,
+    "disk_type": 0, "disk_color": 0, "gravity_direction": 0, "gravity_change_per": 10,
+    "homing_turn_rate": 2, "homing_delay": 0, "homing_duration": 0,
+    "path_curve": 0, "path_duration": 120,
+    "wave_amplitude": 20, "wave_frequency": 1, "wave_phase_step": 0,
+    "split_after": 60, "split_on_wall": false,
+    "split": { "shot_type": 1, "shot_way_num": 8, "shot_speed": 2 },
+    "emit_interval": 10, "emit_max_shots": 10,
+    "emit": { "shot_type": 1, "shot_way_num": 4, "shot_speed": 1 }
+  }"#;
+
+  #[test]
+  fn test_frontend_split_options() {
+    // 子弾設定はScreen設定を省略できる
+    let options: SettingOptions = serde_json::from_str(FRONTEND_OPTIONS).unwrap();
+    let setting = Setting::new(&options);
+    let split = setting.split.expect("split child");
+    assert_eq!(split.shot_way_num, 8);
+    assert_eq!(split.shot_speed, 2.);
+    // 画面サイズは親から引き継ぐ
+    assert_eq!(split.x_coordinate, 400.);
+    assert_eq!(split.y_coordinate, 400.);
+  }
+
+  #[test]
+  fn test_frontend_emit_options() {
+    let options: SettingOptions = serde_json::from_str(FRONTEND_OPTIONS).unwrap();
+    let setting = Setting::new(&options);
+    let emit = setting.emit.expect("emit child");
+    assert_eq!(emit.shot_way_num, 4);
+    assert_eq!(emit.shot_speed, 1.);
+    assert_eq!(emit.x_coordinate, 400.);
+    // 子弾設定自体も単独で変換できる
+    let child: SettingOptions = serde_json::from_str(r#"{ "shot_type": 1, "shot_way_num": 4, "shot_speed": 1 }"#).unwrap();
+    assert_eq!(child.shot_way_num, Some(4));
+    assert!(child.canvas_id.is_empty());
+  }
+
+  #[test]
+  fn test_child_laser_becomes_circle() {
+    let options = SettingOptions {
+      shot_type: Some(3),
+      shot_way_num: Some(5),
+      split: Some(Box::new(SettingOptions {
+        shot_type: Some(3),
+        shot_way_num: Some(5),
+        ..SettingOptions::default()
+      })),
+      ..SettingOptions::default()
+    };
+    let setting = Setting::new(&options);
+    assert!(matches!(setting.shot_type, ShotType::Laser));
+    let split = setting.split.expect("split child");
+    assert!(matches!(split.shot_type, ShotType::Circle));
+    assert_eq!(split.pattern().generate(0., 0., 0., &mut Rng::new(1)).len(), 5);
+  }
+}
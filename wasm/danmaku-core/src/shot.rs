@@ -0,0 +1,246 @@
+use std::sync::Arc;
+use super::disk::{ Disk, DiskColor, DiskType };
+use super::path::{ Path };
+use super::pool::{ SpawnSpec };
+use super::rng::{ Rng };
+use super::timeline::{ Timeline };
+
+#[derive(Debug, Clone)]
+pub enum ShotType {
+    Random,
+    Circle,
+    Linear,
+    Laser, // 直線レーザー(Circleと同じ向きに並べる)
+    Aimed, // 自機狙い(目標への向きを中心に扇状に並べる)
+    Rotate, // 放射状回転(Circleの基準角度を時間で回す)
+    Swirl,  // 渦巻(1回の発射で1弾、時間で角度が進む)
+    Wall,   // 撃ち降ろし(画面幅に等間隔に並べて真下に撃つ)
+}
+
+pub fn resolve_shot_type(num: u32) -> ShotType {
+    match num {
+        1 => ShotType::Circle,
+        2 => ShotType::Linear,
+        3 => ShotType::Laser,
+        4 => ShotType::Aimed,
+        5 => ShotType::Rotate,
+        6 => ShotType::Swirl,
+        7 => ShotType::Wall,
+        _ => ShotType::Random,
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum ShotBehavior {
+    Normal,
+    SpeedUp(f64, f64),   // 加速率(%)/加速イテレーション(per, by)
+    SpeedDown(f64, f64), // 減速率(%)/減速イテレーション(per, by)
+    Reflect(Option<u32>), // 画面反射(rest-reflect-count)
+    Random, // ランダム角度
+    Gravity(u32, f64), // 重力弾(向き0|1|2|3, 変化量)
+    Homing(f64, u32, u32, HomingTarget), // 誘導弾(1tickあたりの最大旋回角rad, 開始までのtick, 誘導するtick数(0で無制限), 目標)
+    Path(Path, u32), // パス弾(軌道, 軌道を辿るtick数) 以降は出口の接線方向に直進
+    Wave(f64, f64), // 揺れ弾(進行方向に垂直な振幅px, 1tickあたりの位相変化rad) 初期位相はDisk毎
+    Split(u32, bool), // 分裂弾(分裂までのtick数(0で時間では分裂しない), 壁に当たったら分裂) 子弾はChildren::splitで指定
+    Emit(u32, u32), // 発射台弾(発射間隔tick, 最大発射回数) 子弾はChildren::emitで指定
+    CurvyLaser(u32), // 曲がるレーザー(胴体として残す先頭の軌跡のtick数) 先頭は他の挙動に従って動く
+    Orbit(f64, f64, f64, u32, bool), // 回転弾(初期半径px, 1tickあたりの半径変化px, 1tickあたりの回転角rad, 接線方向に離脱するまでのtick数(0で離脱しない), 発射点の移動に追従するか) 追従しない場合の中心は発射した時点の発射点
+    Wrap(u32, Option<u32>), // 画面端ループ(ループする辺WRAP_*の組み合わせ, 残りループ回数(Noneで無制限)) 反対側の辺から再登場する
+    Curve(f64, f64, f64), // 曲がる弾(1tickあたりの回転角rad, 1tickあたりの回転角の変化rad, 回転角の上限rad(0で上限なし)) 進行方向を回す
+}
+
+// 画面端ループで反対側に移す辺(ビットの組み合わせ)
+pub const WRAP_LEFT: u32 = 1;
+pub const WRAP_RIGHT: u32 = 2;
+pub const WRAP_TOP: u32 = 4;
+pub const WRAP_BOTTOM: u32 = 8;
+pub const WRAP_ALL: u32 = WRAP_LEFT | WRAP_RIGHT | WRAP_TOP | WRAP_BOTTOM;
+
+/**
+ * 誘導弾の目標
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum HomingTarget {
+    Player,          // 自機
+    Point(f64, f64), // 固定座標
+}
+
+pub fn resolve_shot_behavior(num: u32) -> ShotBehavior {
+    match num {
+        1 => ShotBehavior::SpeedUp(0., 0.),
+        2 => ShotBehavior::SpeedDown(0., 0.),
+        3 => ShotBehavior::Reflect(None),
+        4 => ShotBehavior::Random,
+        // 5(スリープ弾)は毎tickの挙動ではなくSetting::timelineで段階に変換する
+        6 => ShotBehavior::Gravity(0, 0.1),
+        7 => ShotBehavior::Homing(0., 0, 0, HomingTarget::Player),
+        8 => ShotBehavior::Path(Path::default(), 0),
+        9 => ShotBehavior::Wave(0., 0.),
+        10 => ShotBehavior::Split(0, false),
+        11 => ShotBehavior::Emit(0, 0),
+        12 => ShotBehavior::CurvyLaser(0),
+        13 => ShotBehavior::Orbit(0., 0., 0., 0, false),
+        14 => ShotBehavior::Wrap(WRAP_ALL, None),
+        15 => ShotBehavior::Curve(0., 0., 0.),
+        _ => ShotBehavior::Normal,
+    }
+}
+
+/**
+ * 1回の発射で生成するDiskの並び
+ * EventThreadの発射と分裂弾の子弾で共通
+ */
+#[derive(Debug, Clone)]
+pub struct ShotPattern {
+    pub shot_type: ShotType,
+    pub way_num: u32,
+    pub speed: f64,
+    pub disk_type: DiskType,
+    pub disk_size: f64,
+    pub disk_color: DiskColor,
+    pub phase: f64,      // 揺れ弾の初期位相(rad)
+    pub phase_step: f64, // 揺れ弾のWAY毎の位相ずれ(rad)
+    pub spread: f64, // Linear/Aimedの扇の広がり(rad)
+    pub center: f64, // Linearの扇の中心方向(rad、angle_offsetに加える)
+    pub inclusive: bool,  // Linearの扇の両端に撃つか(falseでは右端を含まない)
+    pub random_fan: bool, // Linear/Aimedで扇の範囲内にランダムに撃つか
+    pub width: f64,  // Wallを並べる画面幅
+    pub layers: u32,      // 同じ並びを速度違いで重ねる数(1で重ねない)
+    pub layer_speed_step: f64, // 重ねる毎に加える速度
+}
+
+impl ShotPattern {
+    /**
+     * (x, y)から発射するDisk一覧
+     * angle_offsetはCircle/Linear/Rotateの基準角度、Aimedの扇の中心、Swirlの進み具合(Random/Wallでは無視)
+     * layersが2以上の場合は同じ並び(ランダムな向きも含む)をlayer_speed_step刻みの速度で重ねる
+     * LaserはDiskを生成しない(laser_anglesを使う)
+     */
+    pub fn generate(&self, x: f64, y: f64, angle_offset: f64, rng: &mut Rng) -> Vec<Disk> {
+        let formation = self.formation(x, y, angle_offset, rng);
+        (0..self.layers.max(1))
+            .flat_map(|layer| {
+                let speed = self.speed + self.layer_speed_step * layer as f64;
+                formation.iter().map(move |&disk| Disk { speed, ..disk })
+            })
+            .collect()
+    }
+
+    /**
+     * 1層分の並び
+     */
+    fn formation(&self, x: f64, y: f64, angle_offset: f64, rng: &mut Rng) -> Vec<Disk> {
+        let way_num = self.way_num as usize;
+        let disk = |i: usize, angle: f64| {
+            Disk::new(x, y, self.disk_type, self.disk_size, angle, self.speed, self.disk_color)
+                .with_phase(self.phase + self.phase_step * i as f64)
+        };
+        match self.shot_type {
+            ShotType::Circle | ShotType::Rotate => {
+                let degree = 360. / (self.way_num as f64);
+                (0..way_num)
+                    .map(|i| disk(i, std::f64::consts::PI * ((degree * i as f64) / 180.) + angle_offset))
+                    .collect()
+            },
+            ShotType::Linear => {
+                self.fan_angles(self.center + angle_offset, self.inclusive, rng)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, angle)| disk(i, angle))
+                    .collect()
+            },
+            ShotType::Random => {
+                (0..way_num)
+                    .map(|i| {
+                        let degree = rng.gen_range(0., 1.);
+                        disk(i, std::f64::consts::PI * 180. * degree)
+                    })
+                    .collect()
+            },
+            ShotType::Aimed => {
+                self.fan_angles(angle_offset, true, rng)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, angle)| disk(i, angle))
+                    .collect()
+            },
+            ShotType::Swirl => {
+                // 旧版の渦巻と同じくWAY数が少ないほど速く回る
+                let way = self.way_num.max(1) as f64;
+                vec![disk(0, angle_offset * (1. + 360. / (way * way)))]
+            },
+            ShotType::Wall => {
+                let span = self.width / (self.way_num as f64);
+                (0..way_num)
+                    .map(|i| Disk { x: (i as f64) * span + (span / 2.), ..disk(i, 0.) })
+                    .collect()
+            },
+            ShotType::Laser => vec![], // レーザーはThreadが別に生成する(子弾設定ではCircleに置き換える)
+        }
+    }
+
+    /**
+     * centerを中心にspreadの範囲に並べた各WAYの向き
+     * inclusiveでは両端を含めて等間隔(1WAYは中心)、そうでなければ左端から幅/WAY数ずつ並べる
+     */
+    fn fan_angles(&self, center: f64, inclusive: bool, rng: &mut Rng) -> Vec<f64> {
+        let way_num = self.way_num as usize;
+        let start = center - self.spread / 2.;
+        if self.random_fan {
+            return (0..way_num)
+                .map(|_| rng.gen_range(start, start + self.spread))
+                .collect()
+        }
+        let step = match (inclusive, way_num) {
+            (true, 0..=1) => return vec![center; way_num],
+            (true, _) => self.spread / (way_num - 1) as f64,
+            (false, _) => self.spread / way_num as f64,
+        };
+        (0..way_num)
+            .map(|i| start + step * i as f64)
+            .collect()
+    }
+
+    /**
+     * Laserの各WAYの向き(Circleと同じ並び)
+     */
+    pub fn laser_angles(&self, angle_offset: f64) -> Vec<f64> {
+        let degree = 360. / (self.way_num as f64);
+        (0..self.way_num)
+            .map(|i| std::f64::consts::PI * ((degree * i as f64) / 180.) + angle_offset)
+            .collect()
+    }
+}
+
+/**
+ * Diskから発射される子弾の設定
+ * 子弾もさらに子弾を持てる(深さはsetting::MAX_CHILD_DEPTHまで)
+ */
+#[derive(Debug, Clone)]
+pub struct ChildSpec {
+    pub pattern: ShotPattern,
+    pub behavior: Vec<ShotBehavior>,
+    pub timeline: Timeline,
+    pub children: Children,
+}
+
+impl ChildSpec {
+    /**
+     * 子弾をDiskPoolに追加する時の設定(優先度は親から引き継ぐ)
+     */
+    pub fn spawn_spec(&self, priority: i32) -> SpawnSpec {
+        SpawnSpec::new(self.behavior.clone())
+            .with_priority(priority)
+            .with_children(self.children.clone())
+            .with_timeline(self.timeline.clone())
+    }
+}
+
+/**
+ * Disk群が持つ子弾設定
+ */
+#[derive(Debug, Clone, Default)]
+pub struct Children {
+    pub split: Option<Arc<ChildSpec>>, // 分裂時に発射する子弾
+    pub emit: Option<Arc<ChildSpec>>,  // 飛行中に一定間隔で発射する子弾
+}
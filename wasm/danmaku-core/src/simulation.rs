@@ -0,0 +1,1805 @@
+use super::clock::{ FixedTimestep };
+use super::event_thread::{ EventThread };
+use super::laser::{ closest_point_on_segment, Laser, LaserPhase };
+use super::grid::{ SpatialGrid, DEFAULT_CELL_SIZE };
+use super::path::{ Path };
+use super::player::{ Player, PlayerEvent, PlayerInput };
+use super::pool::{ resolve_overflow_policy, DiskPool, DiskHandle, DiskView, FLAG_GRAZED, FLAG_HIT, FLAG_TRAIL };
+use super::rectanble::{ Rectangle };
+use super::rng::{ Rng };
+use super::schedule::{ Schedule };
+use super::setting::{ Setting, SettingOptions };
+use super::shot::{ HomingTarget, ShotBehavior, WRAP_BOTTOM, WRAP_LEFT, WRAP_RIGHT, WRAP_TOP };
+use super::vec2d::{ Vec2d };
+use super::{ DISK_NUM, DEFAULT_TICK_RATE, DEFAULT_MAX_CATCH_UP_TICKS };
+
+pub type ThreadId = u32;
+
+/**
+ * 座標更新後のDiskの扱い
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum DiskFate {
+  Keep,    // そのまま
+  Despawn, // 消滅
+  Split,   // 分裂(子弾を発射して消滅)
+  Emit,    // そのまま + 子弾を発射(発射台弾)
+}
+
+/**
+ * 描画に依存しない弾幕シミュレーション
+ */
+#[derive(Debug)]
+pub struct Simulation {
+  width: f64,
+  height: f64,
+  disks: DiskPool,
+  grid: SpatialGrid, // 当たり判定用(毎step再構築)
+  query_buffer: Vec<usize>,
+  split_buffer: Vec<DiskHandle>, // 分裂待ちのDisk
+  emit_buffer: Vec<DiskHandle>,  // 子弾の発射待ちのDisk
+  lasers: Vec<Laser>,
+  schedule: Schedule,
+  clock: FixedTimestep,
+  rng: Rng,
+  player: Player,
+  player_events: Vec<PlayerEvent>, // 直近のadvance/stepで発生した自機イベント
+  hit_count: u32, // 累計被弾数
+  graze_count: u32, // 累計グレイズ数
+}
+
+impl Simulation {
+  /**
+   * 初期化(最初のEventThreadを登録)
+   */
+  pub fn new(options: &SettingOptions) -> Self {
+    let setting = Setting::new(options);
+    let tick_rate = options.tick_rate.unwrap_or(DEFAULT_TICK_RATE);
+    let max_catch_up_ticks = options.max_catch_up_ticks.unwrap_or(DEFAULT_MAX_CATCH_UP_TICKS);
+
+    // Scheduleの初期化と最初のEventThreadを登録
+    let mut schedule = Schedule::new(tick_rate);
+    let thread_id = schedule.generate_id();
+    let thread = EventThread::new(
+      thread_id,
+      setting,
+    );
+    schedule.set_end_at(options.iteration_ms);
+    schedule.subscribe_thread(thread);
+    schedule.refresh_events();
+
+    // 自機は画面下部中央に配置
+    let player = Player::new(
+      options.width / 2.,
+      options.height * 0.85,
+      options.player_speed.unwrap_or(4.),
+      options.player_focus_speed.unwrap_or(2.),
+      options.player_hit_radius.unwrap_or(3.),
+      options.player_graze_radius.unwrap_or(20.),
+    );
+
+    let disk_capacity = options.disk_capacity.unwrap_or(DISK_NUM) as usize;
+    let disks = DiskPool::with_policy(
+      disk_capacity,
+      options.disk_capacity_max.map_or(disk_capacity, |max| max as usize),
+      resolve_overflow_policy(options.overflow_policy.unwrap_or(0)),
+    );
+
+    Simulation {
+      width: options.width,
+      height: options.height,
+      disks,
+      grid: SpatialGrid::new(options.width, options.height, DEFAULT_CELL_SIZE),
+      query_buffer: vec![],
+      split_buffer: vec![],
+      emit_buffer: vec![],
+      lasers: vec![],
+      schedule,
+      clock: FixedTimestep::new(tick_rate, max_catch_up_ticks),
+      rng: Rng::from_seed_or_random(options.seed),
+      player,
+      player_events: vec![],
+      hit_count: 0,
+      graze_count: 0,
+    }
+  }
+
+  pub fn width(&self) -> f64 {
+    self.width
+  }
+
+  pub fn height(&self) -> f64 {
+    self.height
+  }
+
+  /**
+   * 使用中の乱数シード(リプレイ/共有用)
+   */
+  pub fn seed(&self) -> u32 {
+    self.rng.seed()
+  }
+
+  pub fn tick_rate(&self) -> u32 {
+    self.clock.tick_rate()
+  }
+
+  /**
+   * 描画フレームの時刻(ms)までシミュレーションを進める
+   * 自機イベントはこのフレームで実行した全tick分を貯める
+   * 実行したtick数を返す
+   */
+  pub fn advance(&mut self, time: f64) -> u32 {
+    let ticks = self.clock.advance(time);
+    self.player_events.clear();
+    for _ in 0..ticks {
+      self.tick();
+    }
+    ticks
+  }
+
+  /**
+   * 1tick分シミュレーションを進める
+   */
+  pub fn step(&mut self) {
+    self.player_events.clear();
+    self.tick();
+  }
+
+  fn tick(&mut self) {
+    self.schedule.iterate();
+
+    /* 発射点に追従する回転弾の中心を更新 */
+    self.update_emitters();
+
+    /* 自機移動 */
+    self.player.update(self.width, self.height);
+
+    /* スケジュールされたイベントの走査 */
+    let player = (self.player.x, self.player.y);
+    self.schedule.walkthrough_events(&mut self.disks, &mut self.lasers, &mut self.rng, player);
+
+    /* Diskのステータスで座標更新 */
+    self.update_disks();
+
+    /* レーザーの回転/照射終了したものの削除 */
+    self.update_lasers();
+
+    /* 当たり判定用グリッドの再構築 */
+    self.grid.rebuild(&self.disks);
+
+    /* 自機との当たり判定 */
+    self.collide_player();
+  }
+
+  pub fn player(&self) -> &Player {
+    &self.player
+  }
+
+  /**
+   * 自機の入力状態を更新
+   */
+  pub fn set_player_input(&mut self, input: PlayerInput) {
+    self.player.set_input(input);
+  }
+
+  /**
+   * 直近のadvance/stepで発生した自機イベント
+   */
+  pub fn player_events(&self) -> &[PlayerEvent] {
+    &self.player_events
+  }
+
+  /**
+   * 累計被弾数
+   */
+  pub fn hit_count(&self) -> u32 {
+    self.hit_count
+  }
+
+  /**
+   * 累計グレイズ数
+   */
+  pub fn graze_count(&self) -> u32 {
+    self.graze_count
+  }
+
+  /**
+   * 生存しているDisk一覧
+   */
+  pub fn disks(&self) -> impl Iterator<Item = DiskView> + '_ {
+    self.disks.iter()
+  }
+
+  /**
+   * ハンドルからDiskを引く(消滅済みの場合はNone)
+   */
+  pub fn disk(&self, handle: DiskHandle) -> Option<DiskView> {
+    self.disks.get(handle)
+  }
+
+  /**
+   * 点(x, y)から半径radius以内に掛かるDisk一覧
+   */
+  pub fn disks_within_radius(&self, x: f64, y: f64, radius: f64) -> Vec<DiskView> {
+    let mut indices = vec![];
+    self.grid.query_radius(&self.disks, x, y, radius, &mut indices);
+    indices
+      .into_iter()
+      .filter_map(|i| self.disks.view(i))
+      .collect()
+  }
+
+  /**
+   * 矩形に掛かるDisk一覧
+   */
+  pub fn disks_in_rect(&self, rect: &Rectangle) -> Vec<DiskView> {
+    let mut indices = vec![];
+    self.grid.query_rect(&self.disks, rect, &mut indices);
+    indices
+      .into_iter()
+      .filter_map(|i| self.disks.view(i))
+      .collect()
+  }
+
+  /**
+   * 曲がるレーザーの胴体(古い順、最後が先頭の現在位置)
+   */
+  pub fn trail(&self, handle: DiskHandle) -> impl Iterator<Item = Vec2d> + '_ {
+    self.disks.trail(handle)
+  }
+
+  /**
+   * 予告線/照射中のレーザー一覧
+   */
+  pub fn lasers(&self) -> &[Laser] {
+    &self.lasers
+  }
+
+  /**
+   * 生存しているDisk数
+   */
+  pub fn active_disk_count(&self) -> usize {
+    self.disks.len()
+  }
+
+  /**
+   * Diskプールのスロット数(拡張後の値)
+   */
+  pub fn disk_capacity(&self) -> usize {
+    self.disks.capacity()
+  }
+
+  /**
+   * プール満杯で破棄した累計Disk数
+   */
+  pub fn dropped_disk_count(&self) -> u32 {
+    self.disks.dropped_count()
+  }
+
+  /**
+   * プール満杯時に空きを作るため消した累計Disk数
+   */
+  pub fn evicted_disk_count(&self) -> u32 {
+    self.disks.evicted_count()
+  }
+
+  /**
+   * ThreadID一覧
+   */
+  pub fn thread_ids(&self) -> Vec<ThreadId> {
+    self.schedule.threads
+      .iter()
+      .map(|thread| thread.id)
+      .collect()
+  }
+
+  /**
+   * settings作成/更新
+   */
+  pub fn upsert_thread_setting(&mut self, thread_id: Option<ThreadId>, options: &SettingOptions) -> Option<ThreadId> {
+    let setting = Setting::new(options);
+    let iteration_ms = setting.iteration_ms;
+    let thread = match thread_id {
+      Some(id) => {
+        self.schedule
+          .threads
+          .iter()
+          .find(|thread| thread.id == id)
+          .map(|thread| {
+            let mut cloned = thread.clone();
+            cloned.update_setting(setting);
+            cloned
+          })
+      },
+      None => {
+        let thread_id = self.schedule.generate_id();
+        Some(
+          EventThread::new(
+            thread_id,
+            setting,
+          )
+        )
+      },
+    }?;
+    let thread_id = thread.id;
+    self.schedule.set_end_at(iteration_ms);
+    self.schedule.subscribe_thread(thread);
+    self.schedule.refresh_events();
+    Some(thread_id)
+  }
+
+  /**
+   * 生存している全Diskと自機の当たり判定
+   * 被弾した弾は消滅、グレイズは1弾につき1回のみカウント
+   */
+  fn collide_player(&mut self) {
+    let (x, y) = (self.player.x, self.player.y);
+    let radius = self.player.hit_radius.max(self.player.graze_radius);
+    self.grid.query_radius(&self.disks, x, y, radius, &mut self.query_buffer);
+    self.query_buffer.sort_unstable();
+    let disks = &mut self.disks;
+    for &i in self.query_buffer.iter() {
+      // 曲がるレーザーは胴体全体で判定する
+      if disks.flags[i] & FLAG_TRAIL != 0 { continue }
+      let (dx, dy, radius) = (disks.x[i], disks.y[i], disks.disk_size[i] / 2.);
+      if self.player.hit_test(dx, dy, radius) {
+        self.hit_count += 1;
+        self.player_events.push(PlayerEvent::Hit { x: dx, y: dy });
+        disks.despawn(i);
+      } else if disks.flags[i] & FLAG_GRAZED == 0 && self.player.graze_test(dx, dy, radius) {
+        disks.flags[i] |= FLAG_GRAZED;
+        self.graze_count += 1;
+        self.player_events.push(PlayerEvent::Graze { x: dx, y: dy });
+      }
+    }
+
+    // 曲がるレーザーは胴体の線分列と判定し、消滅させずに1本につき1回のみ被弾
+    for i in 0..disks.capacity() {
+      if disks.flags[i] & FLAG_TRAIL == 0 || !disks.is_alive(i) { continue }
+      let body = (disks.disk_size[i] / 2.).max(0.);
+      let trail = &disks.trails[i];
+      // 胴体上で自機に最も近い点とその距離の2乗
+      let (distance_sq, (cx, cy)) = trail
+        .iter()
+        .zip(trail.iter().skip(1))
+        .map(|(a, b)| closest_point_on_segment(x, y, a.x, a.y, b.x, b.y))
+        // 発射直後で軌跡が1点のみの場合も判定できるように先頭の点も含める
+        .chain(trail.iter().take(1).map(|a| (a.x, a.y)))
+        .map(|(cx, cy)| ((cx - x).powi(2) + (cy - y).powi(2), (cx, cy)))
+        .fold((f64::INFINITY, (x, y)), |min, point| if point.0 < min.0 { point } else { min });
+      let hit_r = body + self.player.hit_radius;
+      let graze_r = body + self.player.graze_radius;
+      if disks.flags[i] & FLAG_HIT == 0 && distance_sq < hit_r * hit_r {
+        disks.flags[i] |= FLAG_HIT;
+        self.hit_count += 1;
+        self.player_events.push(PlayerEvent::Hit { x: cx, y: cy });
+      } else if disks.flags[i] & (FLAG_GRAZED | FLAG_HIT) == 0 && distance_sq < graze_r * graze_r {
+        disks.flags[i] |= FLAG_GRAZED;
+        self.graze_count += 1;
+        self.player_events.push(PlayerEvent::Graze { x: cx, y: cy });
+      }
+    }
+
+    // レーザーは消滅させず、1本につき1回のみ被弾
+    for laser in self.lasers.iter_mut() {
+      if !laser.hit && laser.hit_test(x, y, self.player.hit_radius) {
+        laser.hit = true;
+        self.hit_count += 1;
+        let (cx, cy) = laser.closest_point(x, y);
+        self.player_events.push(PlayerEvent::Hit { x: cx, y: cy });
+      }
+    }
+  }
+
+  fn update_lasers(&mut self) {
+    for laser in self.lasers.iter_mut() {
+      laser.update();
+    }
+    self.lasers.retain(|laser| laser.phase() != LaserPhase::Finished);
+  }
+
+  /**
+   * 反射時処理
+   * 画面外に出て消滅させるべき場合はtrueを返す
+   */
+  fn on_reflect(disks: &mut DiskPool, i: usize, width: f64, height: f64) -> bool {
+    let size = disks.disk_size[i];
+    let should_reflect = disks.reflect_count[i].unwrap_or(0) > 0;
+    let reflect_behavior = disks.groups[disks.group[i] as usize].behavior
+      .iter()
+      .find(|&sb| matches!(sb, ShotBehavior::Reflect(_)));
+    match (should_reflect, reflect_behavior) {
+      (true, Some(ShotBehavior::Reflect(Some(_)))) => {
+        // X軸方向
+        if disks.x[i] - size < 0. || disks.x[i] + size > width {
+          disks.x[i] -= disks.vx[i];
+          disks.vx[i] = -disks.vx[i];
+          disks.reflect_count[i] = disks.reflect_count[i].map(|num| num - 1);
+        }
+        // Y軸方向
+        if disks.y[i] - size < 0. || disks.y[i] + size > height {
+          disks.y[i] -= disks.vy[i];
+          disks.vy[i] = -disks.vy[i];
+          disks.reflect_count[i] = disks.reflect_count[i].map(|num| num - 1);
+        }
+        false
+      }
+      _ => {
+        // 通常弾の場合
+        let (x, y) = (disks.x[i], disks.y[i]);
+        x + size < 0. || x - size > width || y + size < 0. || y - size > height
+      }
+    }
+  }
+
+  /**
+   * 画面端ループ
+   * edgesの辺から完全に出たDiskを反対側の辺の外側に移す(残り回数がなければそのまま)
+   */
+  fn on_wrap(disks: &mut DiskPool, i: usize, edges: u32, width: f64, height: f64) {
+    if disks.wrap_count[i] == Some(0) { return }
+    let size = disks.disk_size[i];
+    let (x, y) = (disks.x[i], disks.y[i]);
+    let (dx, dy) = if edges & WRAP_LEFT != 0 && x + size < 0. {
+      (width + 2. * size, 0.)
+    } else if edges & WRAP_RIGHT != 0 && x - size > width {
+      (-width - 2. * size, 0.)
+    } else if edges & WRAP_TOP != 0 && y + size < 0. {
+      (0., height + 2. * size)
+    } else if edges & WRAP_BOTTOM != 0 && y - size > height {
+      (0., -height - 2. * size)
+    } else {
+      return
+    };
+    disks.x[i] += dx;
+    disks.y[i] += dy;
+    disks.wrap_count[i] = disks.wrap_count[i].map(|num| num - 1);
+  }
+
+  /**
+   * Diskのステータスに従って座標を更新
+   * playerは誘導弾の目標にする自機座標
+   */
+  fn update_disk(disks: &mut DiskPool, i: usize, width: f64, height: f64, player: (f64, f64)) -> DiskFate {
+    disks.age[i] += 1;
+    let mut drift = (0., 0.); // 速度とは別に加える移動量(揺れ弾)
+    let mut split = false;
+    let mut split_on_wall = false;
+    let mut emit = false;
+    let mut trail_length = None;
+    let mut wrap_edges = 0;
+
+    // ageに応じた段階(段階に入ったtickで速度/向きを変える)
+    let group = &disks.groups[disks.group[i] as usize];
+    let stage = group.timeline.stage_at(disks.age[i]);
+    let stop = stage.is_some_and(|(stage, _)| stage.stop);
+    if let Some((stage, true)) = stage.filter(|(stage, _)| stage.speed.is_some() || stage.aim.is_some()) {
+      if let Some(speed) = stage.speed {
+        disks.speed[i] = speed;
+      }
+      if let Some(target) = stage.aim {
+        let (tx, ty) = match target {
+          HomingTarget::Player => player,
+          HomingTarget::Point(x, y) => (x, y),
+        };
+        disks.angle[i] = (tx - disks.x[i]).atan2(ty - disks.y[i]);
+      }
+      let vec2d = Vec2d::new(disks.angle[i], disks.speed[i]);
+      disks.vx[i] = vec2d.x;
+      disks.vy[i] = vec2d.y;
+    }
+    let stage_behavior = stage.map_or(&[][..], |(stage, _)| &stage.behavior[..]);
+
+    // TODO: ShotBehavior用の解析関数作る
+    for &sb in group.behavior.iter().chain(stage_behavior) {
+      match sb {
+        ShotBehavior::SpeedDown(_, per) => {
+          disks.speed[i] -= disks.speed[i] * per;
+          let vec2d = Vec2d::new(disks.angle[i], disks.speed[i]);
+          disks.vx[i] = vec2d.x;
+          disks.vy[i] = vec2d.y;
+        },
+        ShotBehavior::SpeedUp(_, per) => {
+          disks.speed[i] += disks.speed[i] * per;
+          let vec2d = Vec2d::new(disks.angle[i], disks.speed[i]);
+          disks.vx[i] = vec2d.x;
+          disks.vy[i] = vec2d.y;
+        },
+        // 重力減衰/加速
+        ShotBehavior::Gravity(direction, by) => {
+          let angle = std::f64::consts::PI * (90. * direction as f64) / 180.;
+          let vec2d = Vec2d::new(angle, disks.speed[i] * by);
+          disks.vx[i] += vec2d.x;
+          disks.vy[i] += vec2d.y;
+        }
+        // 誘導
+        ShotBehavior::Homing(turn_rate, delay, duration, target) => {
+          let age = disks.age[i];
+          if age <= delay || (duration > 0 && age > delay + duration) { continue }
+          let (tx, ty) = match target {
+            HomingTarget::Player => player,
+            HomingTarget::Point(x, y) => (x, y),
+          };
+          let target_angle = (tx - disks.x[i]).atan2(ty - disks.y[i]);
+          disks.angle[i] = turn_toward(disks.angle[i], target_angle, turn_rate);
+          let vec2d = Vec2d::new(disks.angle[i], disks.speed[i]);
+          disks.vx[i] = vec2d.x;
+          disks.vy[i] = vec2d.y;
+        },
+        // 進行方向を回す(回転角は経過tickに応じて変化し、上限で頭打ち)
+        ShotBehavior::Curve(omega, alpha, max_omega) => {
+          let mut omega = omega + alpha * (disks.age[i] - 1) as f64;
+          if max_omega > 0. {
+            omega = omega.clamp(-max_omega, max_omega);
+          }
+          disks.angle[i] += omega;
+          let vec2d = Vec2d::new(disks.angle[i], disks.speed[i]);
+          disks.vx[i] = vec2d.x;
+          disks.vy[i] = vec2d.y;
+        },
+        // 進行方向に垂直に揺らす(前tickとのオフセットの差分だけ動かす)
+        ShotBehavior::Wave(amplitude, omega) => {
+          let theta = omega * disks.age[i] as f64 + disks.phase[i];
+          let offset = amplitude * (theta.sin() - (theta - omega).sin());
+          let (sin, cos) = disks.angle[i].sin_cos();
+          drift.0 += offset * cos;
+          drift.1 -= offset * sin;
+        },
+        // 分裂
+        ShotBehavior::Split(after, on_wall) => {
+          split |= after > 0 && disks.age[i] >= after;
+          split_on_wall |= on_wall;
+        },
+        // 一定間隔で子弾を発射(最大発射回数まで)
+        ShotBehavior::Emit(interval, max_shots) => {
+          emit |= interval > 0
+            && disks.age[i].is_multiple_of(interval)
+            && disks.emit_count[i] < max_shots;
+        },
+        // 画面端ループ(移動後に判定)
+        ShotBehavior::Wrap(edges, _) => {
+          wrap_edges |= edges;
+        },
+        // 曲がるレーザー(移動後に軌跡を記録)
+        ShotBehavior::CurvyLaser(length) => {
+          trail_length = Some(length as usize);
+        },
+        // 軌道に沿って移動(速度は次の軌道上の点までの差分)
+        ShotBehavior::Path(path, duration) => {
+          let age = disks.age[i];
+          let (ox, oy, oa) = (disks.origin_x[i], disks.origin_y[i], disks.origin_angle[i]);
+          if age <= duration {
+            let t = age as f64 / duration as f64;
+            let next = Path::to_world(path.point(t), ox, oy, oa);
+            disks.vx[i] = next.x - disks.x[i];
+            disks.vy[i] = next.y - disks.y[i];
+            let tangent = Path::to_world(path.tangent(t), 0., 0., oa);
+            if tangent.x != 0. || tangent.y != 0. {
+              disks.angle[i] = tangent.x.atan2(tangent.y);
+            }
+          } else if age == duration + 1 {
+            // 軌道の終点からは出口の接線方向に直進
+            let vec2d = Vec2d::new(disks.angle[i], disks.speed[i]);
+            disks.vx[i] = vec2d.x;
+            disks.vy[i] = vec2d.y;
+          }
+        },
+        // 中心の周りを回る(速度は次の円周上の点までの差分)
+        // 中心は発射した時点の発射点か、追従する場合は発射元Threadの現在の発射点
+        // 位相は回ったtick数で決まり、停止中は進まない
+        // 離脱後は最後の速度のまま接線方向に直進
+        ShotBehavior::Orbit(radius, radius_speed, omega, release, follow) => {
+          if stop { continue }
+          disks.orbit_ticks[i] += 1;
+          let ticks = disks.orbit_ticks[i];
+          if release > 0 && ticks > release { continue }
+          let center = match group.emitter {
+            Some((_, center)) if follow => center,
+            _ => Vec2d { x: disks.origin_x[i], y: disks.origin_y[i] },
+          };
+          let r = (radius + radius_speed * ticks as f64).max(0.);
+          let theta = disks.origin_angle[i] + omega * ticks as f64;
+          let next = Vec2d::new(theta, r);
+          disks.vx[i] = center.x + next.x - disks.x[i];
+          disks.vy[i] = center.y + next.y - disks.y[i];
+          if disks.vx[i] != 0. || disks.vy[i] != 0. {
+            disks.angle[i] = disks.vx[i].atan2(disks.vy[i]);
+          }
+          disks.speed[i] = disks.vx[i].hypot(disks.vy[i]);
+        },
+        _ => (),
+      }
+    }
+
+    if split { return DiskFate::Split }
+    // 停止中も移動以外(軌跡の更新、画面端の判定、子弾の発射)は行う
+    if !stop {
+      disks.x[i] += disks.vx[i] + drift.0;
+      disks.y[i] += disks.vy[i] + drift.1;
+    }
+
+    let size = disks.disk_size[i];
+    let (x, y) = (disks.x[i], disks.y[i]);
+    if let Some(length) = trail_length {
+      let trail = &mut disks.trails[i];
+      trail.push_back(Vec2d { x, y });
+      while trail.len() > length.max(1) {
+        trail.pop_front();
+      }
+    }
+    if wrap_edges != 0 {
+      Simulation::on_wrap(disks, i, wrap_edges, width, height);
+    }
+    let (x, y) = (disks.x[i], disks.y[i]);
+    if split_on_wall && (x - size < 0. || x + size > width || y - size < 0. || y + size > height) {
+      return DiskFate::Split
+    }
+    if Simulation::on_reflect(disks, i, width, height) {
+      // 曲がるレーザーは胴体が画面内に残っている間は消さない
+      let body_on_screen = disks.trails[i]
+        .iter()
+        .any(|p| p.x + size >= 0. && p.x - size <= width && p.y + size >= 0. && p.y - size <= height);
+      if body_on_screen { DiskFate::Keep } else { DiskFate::Despawn }
+    } else if emit {
+      DiskFate::Emit
+    } else {
+      DiskFate::Keep
+    }
+  }
+
+  /**
+   * 分裂弾を消滅させ、その位置から子弾を発射する
+   * 子弾のパターンは親の進行方向を基準にする
+   */
+  fn split_disk(&mut self, handle: DiskHandle) {
+    // 同じstepで発射された子弾による追い出しで、既に消えている場合がある
+    let disk = match self.disks.get(handle) {
+      Some(disk) => disk,
+      None => return,
+    };
+    let index = handle.index as usize;
+    let group = &self.disks.groups[self.disks.group[index] as usize];
+    let (priority, spec) = (group.priority, group.children.split.clone());
+    self.disks.despawn(index);
+
+    if let Some(spec) = spec {
+      let children = spec.pattern.generate(disk.x, disk.y, disk.angle, &mut self.rng);
+      self.disks.spawn_group(spec.spawn_spec(priority), children);
+    }
+  }
+
+  /**
+   * 発射台弾の位置から子弾を発射する
+   * 子弾のパターンは親の進行方向を基準にする
+   */
+  fn emit_disk(&mut self, handle: DiskHandle) {
+    let disk = match self.disks.get(handle) {
+      Some(disk) => disk,
+      None => return,
+    };
+    let index = handle.index as usize;
+    let group = &self.disks.groups[self.disks.group[index] as usize];
+    let (priority, spec) = match &group.children.emit {
+      Some(spec) => (group.priority, spec.clone()),
+      None => return,
+    };
+    self.disks.emit_count[index] += 1;
+
+    let children = spec.pattern.generate(disk.x, disk.y, disk.angle, &mut self.rng);
+    self.disks.spawn_group(spec.spawn_spec(priority), children);
+  }
+
+  /**
+   * 発射元Threadを持つグループの中心を、Threadの現在の発射点に更新する
+   * Threadが削除された場合は最後の発射点のまま
+   */
+  fn update_emitters(&mut self) {
+    let time = self.schedule.iteration();
+    let threads = &self.schedule.threads;
+    for (id, center) in self.disks.groups.iter_mut().filter_map(|group| group.emitter.as_mut()) {
+      if let Some(thread) = threads.iter().find(|thread| thread.id == *id) {
+        *center = thread.setting.origin(time);
+      }
+    }
+  }
+
+  /**
+   * 登録している全Diskの座標を更新し、画面外に出たものを消滅させる
+   */
+  fn update_disks(&mut self) {
+    let width = self.width;
+    let height = self.height;
+    let player = (self.player.x, self.player.y);
+    self.split_buffer.clear();
+    self.emit_buffer.clear();
+    for i in 0..self.disks.capacity() {
+      if !self.disks.is_alive(i) { continue }
+      match Simulation::update_disk(&mut self.disks, i, width, height, player) {
+        DiskFate::Keep => (),
+        DiskFate::Despawn => { self.disks.despawn(i); },
+        DiskFate::Split => self.split_buffer.push(self.disks.handle_of(i)),
+        DiskFate::Emit => self.emit_buffer.push(self.disks.handle_of(i)),
+      }
+    }
+
+    // 子弾の発射は全Disk更新後にまとめて行う(子弾は次のstepから動く)
+    let emit_buffer = std::mem::take(&mut self.emit_buffer);
+    for &handle in emit_buffer.iter() {
+      self.emit_disk(handle);
+    }
+    self.emit_buffer = emit_buffer;
+
+    let split_buffer = std::mem::take(&mut self.split_buffer);
+    for &handle in split_buffer.iter() {
+      self.split_disk(handle);
+    }
+    self.split_buffer = split_buffer;
+  }
+}
+
+/**
+ * angleをtarget_angleに向けて最大max_turnだけ回す(近い方向に回る)
+ */
+fn turn_toward(angle: f64, target_angle: f64, max_turn: f64) -> f64 {
+  use std::f64::consts::PI;
+  let diff = (target_angle - angle + PI).rem_euclid(2. * PI) - PI;
+  angle + diff.clamp(-max_turn, max_turn)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::disk::{ DiskColor };
+
+  fn options() -> SettingOptions {
+    SettingOptions {
+      canvas_id: String::from("canvas"),
+      theme: 0,
+      width: 800.,
+      height: 800.,
+      iteration_ms: 2000,
+      start_at: 0,
+      end_at: 2000,
+      shot_type: Some(1),
+      shot_way_num: Some(8),
+      shot_interval: Some(1000),
+      ..SettingOptions::default()
+    }
+  }
+
+  #[test]
+  fn test_step_spawns_and_moves_disks() {
+    let mut simulation = Simulation::new(&options());
+    assert_eq!(simulation.active_disk_count(), 0);
+
+    // 1000ms間隔 -> 60frで発射
+    for _ in 0..60 {
+      simulation.step();
+    }
+    assert_eq!(simulation.active_disk_count(), 8);
+
+    let before = simulation.disks().map(|d| (d.x, d.y)).collect::<Vec<_>>();
+    simulation.step();
+    let after = simulation.disks().map(|d| (d.x, d.y)).collect::<Vec<_>>();
+    assert_ne!(before, after);
+  }
+
+  #[test]
+  fn test_schedule_timing_is_independent_of_tick_rate() {
+    // 2000msで1周、0..1000msの間500ms毎に発射 -> 0.5s, 1.0s, (1周後)2.5s, 3.0sに発射
+    for &tick_rate in &[30, 60, 120] {
+      let mut simulation = Simulation::new(&SettingOptions {
+        tick_rate: Some(tick_rate),
+        shot_way_num: Some(1),
+        shot_speed: Some(0.),
+        shot_interval: Some(500),
+        end_at: 1000,
+        ..options()
+      });
+      let mut spawned_at = vec![];
+      for tick in 1..=(tick_rate * 4) {
+        let before = simulation.active_disk_count();
+        simulation.step();
+        if simulation.active_disk_count() > before {
+          spawned_at.push(tick as f64 / tick_rate as f64);
+        }
+      }
+      assert_eq!(spawned_at, vec![0.5, 1.0, 2.5, 3.0], "{}tick/s", tick_rate);
+    }
+  }
+
+  #[test]
+  fn test_simulation_is_send() {
+    // ネイティブのツールでワーカースレッドに渡せる
+    fn assert_send<T: Send>() {}
+    assert_send::<Simulation>();
+  }
+
+  #[test]
+  fn test_player_hit() {
+    // 自機の真上から真下に向けて発射
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_way_num: Some(1),
+      shot_speed: Some(10.),
+      y_coordinate: Some(400.),
+      ..options()
+    });
+    let mut events = vec![];
+    for _ in 0..120 {
+      simulation.step();
+      events.extend_from_slice(simulation.player_events());
+    }
+    assert_eq!(simulation.hit_count(), 1);
+    assert_eq!(simulation.graze_count(), 1);
+    assert_eq!(events.len(), 2);
+    assert!(matches!(events[0], PlayerEvent::Graze { .. }));
+    assert!(matches!(events[1], PlayerEvent::Hit { .. }));
+  }
+
+  #[test]
+  fn test_player_events_are_kept_across_ticks() {
+    // 60frと120frに発射した弾のグレイズ/被弾(計4回)を、リフレッシュレートによらず全て拾う
+    let options = || SettingOptions {
+      shot_way_num: Some(1),
+      shot_speed: Some(10.),
+      y_coordinate: Some(400.),
+      ..options()
+    };
+    let mut simulation = Simulation::new(&options());
+    let mut expected = vec![];
+    for _ in 0..150 {
+      simulation.step();
+      expected.extend_from_slice(simulation.player_events());
+    }
+    assert_eq!(expected.len(), 4);
+
+    for &refresh_rate in &[30, 60, 120] {
+      let mut simulation = Simulation::new(&options());
+      let frame_ms = 1000. / refresh_rate as f64;
+      let mut events = vec![];
+      for frame in 0..=(refresh_rate * 5 / 2) {
+        simulation.advance(frame_ms * frame as f64);
+        events.extend_from_slice(simulation.player_events());
+      }
+      assert_eq!(events, expected, "{}Hz", refresh_rate);
+    }
+  }
+
+  #[test]
+  fn test_player_graze_counts_once() {
+    // 自機の横をかすめるように発射
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_way_num: Some(1),
+      shot_speed: Some(2.),
+      x_coordinate: Some(412.),
+      y_coordinate: Some(600.),
+      ..options()
+    });
+    for _ in 0..120 {
+      simulation.step();
+    }
+    assert_eq!(simulation.hit_count(), 0);
+    assert_eq!(simulation.graze_count(), 1);
+  }
+
+  #[test]
+  fn test_spatial_queries() {
+    let mut simulation = Simulation::new(&options());
+    // 発射後31fr -> 中心から半径31の円周上に8弾
+    for _ in 0..90 {
+      simulation.step();
+    }
+    assert_eq!(simulation.disks_within_radius(400., 400., 30.).len(), 8);
+    assert_eq!(simulation.disks_within_radius(400., 400., 25.).len(), 0);
+    // 中心から真下(angle = 0)へ向かう弾のみ
+    let rect = Rectangle::new(395., 426., 10., 10.);
+    assert_eq!(simulation.disks_in_rect(&rect).len(), 1);
+  }
+
+  #[test]
+  fn test_disk_handle_invalidated_on_despawn() {
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_speed: Some(100.),
+      ..options()
+    });
+    for _ in 0..60 {
+      simulation.step();
+    }
+    let handles = simulation.disks().map(|d| d.handle).collect::<Vec<_>>();
+    assert_eq!(handles.len(), 8);
+    assert!(simulation.disk(handles[0]).is_some());
+    for _ in 0..10 {
+      simulation.step();
+    }
+    assert!(handles.iter().all(|&h| simulation.disk(h).is_none()));
+  }
+
+  #[test]
+  fn test_advance_is_independent_of_refresh_rate() {
+    let run = |refresh_rate: u32| {
+      let mut simulation = Simulation::new(&options());
+      let frame_ms = 1000. / refresh_rate as f64;
+      for i in 0..=(refresh_rate * 2) {
+        simulation.advance(frame_ms * i as f64);
+      }
+      // 浮動小数の誤差で取りこぼした端数を半tick分の猶予で吸収する
+      simulation.advance(2000. + 1000. / 120.);
+      simulation.disks().map(|d| (d.x, d.y)).collect::<Vec<_>>()
+    };
+    let base = run(60);
+    assert_eq!(base.len(), 16);
+    assert_eq!(base, run(120));
+    assert_eq!(base, run(144));
+    assert_eq!(base, run(30));
+  }
+
+  #[test]
+  fn test_tick_rate_scales_shot_interval() {
+    // 120tick/sでは1000ms間隔 = 120tick
+    let mut simulation = Simulation::new(&SettingOptions {
+      tick_rate: Some(120),
+      ..options()
+    });
+    for _ in 0..119 {
+      simulation.step();
+    }
+    assert_eq!(simulation.active_disk_count(), 0);
+    simulation.step();
+    assert_eq!(simulation.active_disk_count(), 8);
+  }
+
+  #[test]
+  fn test_overflow_is_counted() {
+    let mut simulation = Simulation::new(&SettingOptions {
+      disk_capacity: Some(4),
+      ..options()
+    });
+    for _ in 0..60 {
+      simulation.step();
+    }
+    assert_eq!(simulation.active_disk_count(), 4);
+    assert_eq!(simulation.dropped_disk_count(), 4);
+
+    let mut simulation = Simulation::new(&SettingOptions {
+      disk_capacity: Some(4),
+      disk_capacity_max: Some(16),
+      ..options()
+    });
+    for _ in 0..60 {
+      simulation.step();
+    }
+    assert_eq!(simulation.active_disk_count(), 8);
+    assert_eq!(simulation.disk_capacity(), 8);
+    assert_eq!(simulation.dropped_disk_count(), 0);
+  }
+
+  #[test]
+  fn test_turn_toward() {
+    let deg = |d: f64| d.to_radians();
+    assert!((turn_toward(0., deg(90.), deg(5.)) - deg(5.)).abs() < 1e-9);
+    assert!((turn_toward(0., deg(3.), deg(5.)) - deg(3.)).abs() < 1e-9);
+    // 170° -> -170° は180°をまたいで+方向に回る
+    assert!((turn_toward(deg(170.), deg(-170.), deg(5.)) - deg(175.)).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_homing_steers_after_delay() {
+    // 真下に撃った弾を右側の遠方へ誘導する
+    let homing = |duration: u32| {
+      let mut simulation = Simulation::new(&SettingOptions {
+        shot_way_num: Some(1),
+        shot_behavior: Some(vec![7]),
+        homing_turn_rate: Some(5.),
+        homing_delay: Some(10),
+        homing_duration: Some(duration),
+        homing_x: Some(100_000.),
+        homing_y: Some(400.),
+        ..options()
+      });
+      // 60frで発射(発射したstepでage 1) -> 69fr目でage 10
+      for _ in 0..69 {
+        simulation.step();
+      }
+      simulation
+    };
+    let angle = |simulation: &Simulation| simulation.disks().next().unwrap().angle;
+
+    let mut simulation = homing(0);
+    assert_eq!(angle(&simulation), 0.);
+    simulation.step();
+    assert!((angle(&simulation) - 5f64.to_radians()).abs() < 1e-9);
+    for _ in 0..30 {
+      simulation.step();
+    }
+    assert!((angle(&simulation) - std::f64::consts::FRAC_PI_2).abs() < 0.01);
+
+    // 誘導期間を過ぎたら旋回しない
+    let mut simulation = homing(3);
+    for _ in 0..10 {
+      simulation.step();
+    }
+    assert!((angle(&simulation) - 15f64.to_radians()).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_path_then_exit_tangent() {
+    // 下に撃ち、右に曲がって抜ける軌道
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_way_num: Some(1),
+      shot_speed: Some(2.),
+      shot_behavior: Some(vec![8]),
+      path_curve: Some(1),
+      path_points: Some(vec![0., 30., 0., 60., 30., 60.]),
+      path_duration: Some(30),
+      ..options()
+    });
+    // 60frで発射(発射したstepでage 1) -> 89fr目で軌道の終点
+    for _ in 0..89 {
+      simulation.step();
+    }
+    let disk = simulation.disks().next().unwrap();
+    assert!((disk.x - 430.).abs() < 1e-9 && (disk.y - 460.).abs() < 1e-9);
+
+    simulation.step();
+    let disk = simulation.disks().next().unwrap();
+    assert!((disk.x - 432.).abs() < 1e-9 && (disk.y - 460.).abs() < 1e-9);
+    assert!((disk.angle - std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_wave_oscillates_perpendicular() {
+    // 真下に撃った弾が左右に揺れる(60tick/sで1Hz -> 60tickで1周期)
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_way_num: Some(2),
+      shot_behavior: Some(vec![9]),
+      wave_amplitude: Some(10.),
+      wave_frequency: Some(1.),
+      wave_phase_step: Some(180.),
+      ..options()
+    });
+    for _ in 0..59 {
+      simulation.step();
+    }
+    // 60frで発射(発射したstepでage 1) -> xs[age - 1]
+    let mut xs = vec![];
+    for _ in 0..60 {
+      simulation.step();
+      let disks = simulation.disks().collect::<Vec<_>>();
+      xs.push((disks[0].x, disks[1].x));
+    }
+    // 四分の一周期で振幅分ずれ、一周期で元に戻る
+    assert!((xs[14].0 - 410.).abs() < 1e-9);
+    assert!((xs[44].0 - 390.).abs() < 1e-9);
+    assert!((xs[59].0 - 400.).abs() < 1e-9);
+    assert!((simulation.disks().next().unwrap().y - 460.).abs() < 1e-9);
+    // 位相を180°ずらした2WAY目(真上に発射)は同じ側に揺れる
+    assert!((xs[14].1 - 410.).abs() < 1e-9);
+    assert!((xs[44].1 - 390.).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_orbit_expands_then_releases() {
+    // 4WAYが発射点の周りを半径1px/tickで広がりながら回り、20tickで離脱する
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_way_num: Some(4),
+      shot_behavior: Some(vec![13]),
+      orbit_radius: Some(10.),
+      orbit_radius_speed: Some(1.),
+      orbit_angular_speed: Some(6.),
+      orbit_release: Some(20),
+      ..options()
+    });
+    // 60frで発射(発射したstepでage 1) -> age 10
+    for _ in 0..69 {
+      simulation.step();
+    }
+    for d in simulation.disks() {
+      assert!(((d.x - 400.).hypot(d.y - 400.) - 20.).abs() < 1e-9);
+    }
+    // 1WAY目(真下に発射)は60°回っている
+    let d = simulation.disks().next().unwrap();
+    assert!(((d.x - 400.).atan2(d.y - 400.) - 60f64.to_radians()).abs() < 1e-9);
+
+    // 離脱後は直進する
+    for _ in 0..11 {
+      simulation.step();
+    }
+    let before = simulation.disks().map(|d| (d.x, d.y)).collect::<Vec<_>>();
+    simulation.step();
+    let after = simulation.disks().map(|d| (d.x, d.y)).collect::<Vec<_>>();
+    simulation.step();
+    for ((b, a), d) in before.iter().zip(after.iter()).zip(simulation.disks()) {
+      assert!((d.x - a.0 - (a.0 - b.0)).abs() < 1e-9);
+      assert!((d.y - a.1 - (a.1 - b.1)).abs() < 1e-9);
+      // 半径は広がり続ける
+      assert!((d.x - 400.).hypot(d.y - 400.) > 30.);
+    }
+  }
+
+  #[test]
+  fn test_orbit_follows_emitter() {
+    // 発射後に発射点を(600, 400)へ動かすと、追従する回転弾だけ中心が移る
+    let distance_to = |follow: bool, center: (f64, f64)| {
+      let options = SettingOptions {
+        shot_way_num: Some(1),
+        shot_behavior: Some(vec![13]),
+        orbit_radius: Some(10.),
+        orbit_radius_speed: Some(0.),
+        orbit_angular_speed: Some(6.),
+        orbit_follow: Some(follow),
+        ..options()
+      };
+      let mut simulation = Simulation::new(&options);
+      // 60frで(400, 400)から発射
+      for _ in 0..60 {
+        simulation.step();
+      }
+      let handle = simulation.disks().next().unwrap().handle;
+      let id = simulation.thread_ids()[0];
+      simulation.upsert_thread_setting(Some(id), &SettingOptions { x_coordinate: Some(600.), ..options });
+      for _ in 0..10 {
+        simulation.step();
+      }
+      let d = simulation.disk(handle).unwrap();
+      (d.x - center.0).hypot(d.y - center.1)
+    };
+    assert!((distance_to(true, (600., 400.)) - 10.).abs() < 1e-9);
+    assert!((distance_to(false, (400., 400.)) - 10.).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_orbit_centers_on_spawn_point() {
+    // 発射点が動いても、回転弾は発射した時点の発射点の周りを回り続ける
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_way_num: Some(1),
+      shot_behavior: Some(vec![13]),
+      orbit_radius: Some(10.),
+      orbit_radius_speed: Some(0.),
+      orbit_angular_speed: Some(6.),
+      emitter_motion: Some(1),
+      emitter_keyframes: Some(vec![0., 100., 400., 120., 700., 400.]),
+      ..options()
+    });
+    // 60tick目に(400, 400)から発射 -> 発射点は(600, 400)まで動く
+    for _ in 0..101 {
+      simulation.step();
+    }
+    let d = simulation.disks().next().unwrap();
+    assert!(((d.x - 400.).hypot(d.y - 400.) - 10.).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_orbit_follows_moving_emitter() {
+    // 発射点(0tickに(100, 400)、120tickに(700, 400))に追従して回る
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_way_num: Some(4),
+      shot_behavior: Some(vec![13]),
+      orbit_radius: Some(10.),
+      orbit_radius_speed: Some(0.),
+      orbit_angular_speed: Some(6.),
+      orbit_follow: Some(true),
+      emitter_motion: Some(1),
+      emitter_keyframes: Some(vec![0., 100., 400., 120., 700., 400.]),
+      ..options()
+    });
+    // 60tick目に(400, 400)から発射 -> 100tick目の発射点は(600, 400)
+    for _ in 0..100 {
+      simulation.step();
+    }
+    assert_eq!(simulation.active_disk_count(), 4);
+    for d in simulation.disks() {
+      assert!(((d.x - 600.).hypot(d.y - 400.) - 10.).abs() < 1e-9);
+    }
+  }
+
+  #[test]
+  fn test_orbit_resumes_after_stop() {
+    use crate::setting::{ StageOptions };
+    // 10tick回って10tick止まった後、止まった位置から回り続ける
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_way_num: Some(1),
+      shot_behavior: Some(vec![13]),
+      orbit_radius: Some(10.),
+      orbit_radius_speed: Some(0.),
+      orbit_angular_speed: Some(6.),
+      timeline: Some(vec![
+        StageOptions { start: 11, stop: Some(true), ..StageOptions::default() },
+        StageOptions { start: 21, ..StageOptions::default() },
+      ]),
+      ..options()
+    });
+    // 60frで発射(発射したstepでage 1) -> age 20
+    for _ in 0..79 {
+      simulation.step();
+    }
+    let stopped = simulation.disks().next().unwrap();
+    // 真下に発射して60°回ったところで止まっている
+    assert!(((stopped.x - 400.).atan2(stopped.y - 400.) - 60f64.to_radians()).abs() < 1e-9);
+    simulation.step();
+    let d = simulation.disks().next().unwrap();
+    assert!(((d.x - 400.).atan2(d.y - 400.) - 66f64.to_radians()).abs() < 1e-9);
+    assert!(((d.x - 400.).hypot(d.y - 400.) - 10.).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_emitter_moves_along_keyframes() {
+    // 0tickに(100, 400)、120tickに(700, 400) -> 60tick目の発射は中間点から
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_way_num: Some(1),
+      shot_speed: Some(0.),
+      emitter_motion: Some(1),
+      emitter_keyframes: Some(vec![0., 100., 400., 120., 700., 400.]),
+      ..options()
+    });
+    for _ in 0..121 {
+      simulation.step();
+    }
+    let xs = simulation.disks().map(|d| d.x).collect::<Vec<_>>();
+    assert_eq!(xs, vec![400., 700.]);
+  }
+
+  #[test]
+  fn test_aimed_fan_centers_on_target() {
+    // 右上から自機((400, 600)付近)に向けて3WAY/90°の扇
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_type: Some(4),
+      shot_way_num: Some(3),
+      aim_spread: Some(90.),
+      x_coordinate: Some(600.),
+      y_coordinate: Some(200.),
+      ..options()
+    });
+    for _ in 0..60 {
+      simulation.step();
+    }
+    let player = simulation.player();
+    let aim = (player.x - 600.).atan2(player.y - 200.);
+    let angles = simulation.disks().map(|d| d.angle).collect::<Vec<_>>();
+    assert_eq!(angles.len(), 3);
+    assert!((angles[0] - (aim - std::f64::consts::FRAC_PI_4)).abs() < 1e-9);
+    assert!((angles[1] - aim).abs() < 1e-9);
+    assert!((angles[2] - (aim + std::f64::consts::FRAC_PI_4)).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_aimed_once_per_burst() {
+    // 2回の発射ごとに狙い直す(狙いのブレは狙い直した時のみ)
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_type: Some(4),
+      shot_way_num: Some(1),
+      shot_interval: Some(500),
+      aim_jitter: Some(30.),
+      aim_burst: Some(2),
+      ..options()
+    });
+    let mut angles = vec![];
+    for _ in 0..120 {
+      simulation.step();
+      if let Some(d) = simulation.disks().find(|d| !angles.contains(&d.angle)) {
+        angles.push(d.angle);
+      }
+    }
+    // 30fr毎に4回発射 -> 2種類の向き
+    assert_eq!(simulation.active_disk_count(), 4);
+    assert_eq!(angles.len(), 2);
+  }
+
+  #[test]
+  fn test_legacy_rotate_swirl_wall() {
+    let disks_at = |shot_type: u32| {
+      let mut simulation = Simulation::new(&SettingOptions {
+        shot_type: Some(shot_type),
+        shot_way_num: Some(4),
+        shot_speed: Some(0.),
+        y_coordinate: Some(100.),
+        ..options()
+      });
+      for _ in 0..60 {
+        simulation.step();
+      }
+      simulation.disks().map(|d| (d.x, d.y, d.angle)).collect::<Vec<_>>()
+    };
+    // 放射状回転: 60tick目は1.5°/tick * 60 = 90°回っている
+    let rotate = disks_at(5);
+    assert_eq!(rotate.len(), 4);
+    for (i, &(_, _, angle)) in rotate.iter().enumerate() {
+      assert!((angle - (90. + 90. * i as f64).to_radians()).abs() < 1e-9);
+    }
+    // 渦巻: 1弾のみ、90° * (1 + 360 / 4^2)
+    let swirl = disks_at(6);
+    assert_eq!(swirl.len(), 1);
+    assert!((swirl[0].2 - (90. * 23.5f64).to_radians()).abs() < 1e-9);
+    // 渦巻の弾速はshot_speedによらず、swirl_speed未指定時は旧版と同じ1(発射したstepで1回動く)
+    assert!(((swirl[0].0 - 400.).hypot(swirl[0].1 - 100.) - 1.).abs() < 1e-9);
+    // 撃ち降ろし: 画面幅を4等分した中央から真下
+    let wall = disks_at(7);
+    assert_eq!(wall, vec![(100., 100., 0.), (300., 100., 0.), (500., 100., 0.), (700., 100., 0.)]);
+  }
+
+  #[test]
+  fn test_swirl_speed() {
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_type: Some(6),
+      shot_speed: Some(0.),
+      swirl_speed: Some(3.),
+      ..options()
+    });
+    for _ in 0..60 {
+      simulation.step();
+    }
+    let d = simulation.disks().next().unwrap();
+    assert_eq!(d.speed, 3.);
+    assert!(((d.x - 400.).hypot(d.y - 400.) - 3.).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_linear_fan() {
+    let angles_of = |options: SettingOptions| {
+      let mut simulation = Simulation::new(&SettingOptions {
+        shot_type: Some(2),
+        shot_way_num: Some(3),
+        ..options
+      });
+      for _ in 0..60 {
+        simulation.step();
+      }
+      simulation.disks().map(|d| d.angle.to_degrees()).collect::<Vec<_>>()
+    };
+    let approx = |a: &[f64], b: &[f64]| a.len() == b.len() && a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-9);
+    // 未指定時は従来通り100°を3等分
+    assert!(approx(&angles_of(options()), &[-50., -50. + 100. / 3., -50. + 200. / 3.]));
+    // 右向き(90°)を中心に30°の範囲で両端を含める
+    let narrow = angles_of(SettingOptions {
+      linear_spread: Some(30.),
+      linear_center: Some(90.),
+      linear_inclusive: Some(true),
+      ..options()
+    });
+    assert!(approx(&narrow, &[75., 90., 105.]));
+    // ランダムでも範囲内に収まる
+    let random = angles_of(SettingOptions {
+      linear_spread: Some(180.),
+      linear_random: Some(true),
+      ..options()
+    });
+    assert_eq!(random.len(), 3);
+    assert!(random.iter().all(|a| (-90. ..=90.).contains(a)));
+  }
+
+  #[test]
+  fn test_stacked_layers() {
+    // 4WAYを速度2.0から0.5刻みで3層重ねる
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_way_num: Some(4),
+      shot_speed: Some(2.),
+      stack_layers: Some(3),
+      stack_speed_step: Some(0.5),
+      ..options()
+    });
+    for _ in 0..60 {
+      simulation.step();
+    }
+    let disks = simulation.disks().collect::<Vec<_>>();
+    assert_eq!(disks.len(), 12);
+    for (layer, speed) in [2., 2.5, 3.].iter().enumerate() {
+      for way in 0..4 {
+        let d = &disks[layer * 4 + way];
+        assert_eq!(d.angle, disks[way].angle);
+        assert!(((d.x - 400.).hypot(d.y - 400.) - speed).abs() < 1e-9);
+      }
+    }
+  }
+
+  #[test]
+  fn test_wrap_to_opposite_edge() {
+    // 右向きに撃った弾が右端から左端に1回だけループする
+    let wrap = |wrap_edges: u32| {
+      let mut simulation = Simulation::new(&SettingOptions {
+        shot_type: Some(2),
+        shot_way_num: Some(1),
+        shot_speed: Some(10.),
+        shot_behavior: Some(vec![14]),
+        linear_center: Some(90.),
+        linear_inclusive: Some(true),
+        x_coordinate: Some(700.),
+        y_coordinate: Some(100.),
+        wrap_edges: Some(wrap_edges),
+        wrap_count: Some(1),
+        ..options()
+      });
+      for _ in 0..60 {
+        simulation.step();
+      }
+      // 60frで発射した弾のみ追う
+      let handle = simulation.disks().next().unwrap().handle;
+      let mut xs = vec![];
+      for _ in 0..200 {
+        simulation.step();
+        xs.extend(simulation.disk(handle).map(|d| d.x));
+      }
+      xs
+    };
+    let xs = wrap(WRAP_RIGHT);
+    let wrapped = xs.windows(2).position(|w| w[1] < w[0]).unwrap();
+    assert!(xs[wrapped] > 790. && xs[wrapped + 1] < 10.);
+    // 2回目は消える
+    assert!(xs[wrapped + 1..].windows(2).all(|w| w[1] > w[0]));
+    assert!(*xs.last().unwrap() < 810.);
+    // 右端がループ対象でなければそのまま消える
+    let xs = wrap(WRAP_LEFT | WRAP_TOP | WRAP_BOTTOM);
+    assert!(xs.windows(2).all(|w| w[1] > w[0]));
+  }
+
+  #[test]
+  fn test_curve_accelerates_up_to_clamp() {
+    // 1°/tickから1tick毎に1°ずつ速く回り、3°/tickで頭打ち
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_way_num: Some(1),
+      shot_behavior: Some(vec![15]),
+      curve_angular_velocity: Some(1.),
+      curve_angular_acceleration: Some(1.),
+      curve_max_angular_velocity: Some(3.),
+      ..options()
+    });
+    for _ in 0..59 {
+      simulation.step();
+    }
+    // 60frで発射(発射したstepでage 1)
+    let mut angles = vec![];
+    for _ in 0..5 {
+      simulation.step();
+      angles.push(simulation.disks().next().unwrap().angle.to_degrees());
+    }
+    let expected = [1., 3., 6., 9., 12.];
+    assert!(angles.iter().zip(expected.iter()).all(|(a, b)| (a - b).abs() < 1e-9), "{:?}", angles);
+    // 速度の向きも追従する
+    let d = simulation.disks().next().unwrap();
+    assert!((d.vec2d.x.atan2(d.vec2d.y).to_degrees() - 12.).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_timeline_stages() {
+    use crate::setting::{ StageOptions };
+    // 真横に30tick直進 -> 20tick停止 -> 自機を狙い直して速度3で直進
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_type: Some(2),
+      shot_way_num: Some(1),
+      linear_center: Some(90.),
+      linear_inclusive: Some(true),
+      x_coordinate: Some(100.),
+      y_coordinate: Some(100.),
+      timeline: Some(vec![
+        StageOptions { start: 30, stop: Some(true), ..StageOptions::default() },
+        StageOptions { start: 50, speed: Some(3.), aim: Some(true), ..StageOptions::default() },
+      ]),
+      ..options()
+    });
+    // 60frで発射(発射したstepでage 1)
+    for _ in 0..88 {
+      simulation.step();
+    }
+    let d = simulation.disks().next().unwrap();
+    assert_eq!((d.x, d.y), (129., 100.));
+    for _ in 0..20 {
+      simulation.step();
+    }
+    let d = simulation.disks().next().unwrap();
+    assert_eq!((d.x, d.y), (129., 100.));
+    simulation.step();
+    let d = simulation.disks().next().unwrap();
+    let player = simulation.player();
+    let aim = (player.x - 129.).atan2(player.y - 100.);
+    assert!((d.angle - aim).abs() < 1e-9);
+    assert!((d.speed - 3.).abs() < 1e-9);
+    assert!(((d.x - 129.).hypot(d.y - 100.) - 3.).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_stopped_disk_emits_and_trims_trail() {
+    use crate::setting::{ StageOptions };
+    // 右に飛ぶ発射台弾が4tick後に止まり、止まったまま10tick毎に子弾を撃つ
+    let stop_at_5 = || Some(vec![StageOptions { start: 5, stop: Some(true), ..StageOptions::default() }]);
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_way_num: Some(1),
+      shot_speed: Some(2.),
+      x_coordinate: Some(100.),
+      degree_change_by: Some(std::f64::consts::FRAC_PI_2),
+      shot_behavior: Some(vec![11]),
+      emit_interval: Some(10),
+      emit_max_shots: Some(3),
+      emit: Some(Box::new(SettingOptions {
+        shot_type: Some(1),
+        shot_way_num: Some(1),
+        shot_speed: Some(0.),
+        disk_color: Some(5),
+        ..SettingOptions::default()
+      })),
+      timeline: stop_at_5(),
+      ..options()
+    });
+    for _ in 0..110 {
+      simulation.step();
+    }
+    let xs = simulation.disks()
+      .filter(|d| d.disk_color == DiskColor::Blue)
+      .map(|d| d.x)
+      .collect::<Vec<_>>();
+    assert_eq!(xs, vec![108., 108., 108.]);
+
+    // 止まった曲がるレーザーは胴体が先頭に縮む
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_way_num: Some(1),
+      shot_speed: Some(2.),
+      x_coordinate: Some(100.),
+      degree_change_by: Some(std::f64::consts::FRAC_PI_2),
+      shot_behavior: Some(vec![12]),
+      curvy_length: Some(10),
+      timeline: stop_at_5(),
+      ..options()
+    });
+    for _ in 0..80 {
+      simulation.step();
+    }
+    let head = simulation.disks().next().unwrap();
+    assert_eq!(head.x, 108.);
+    assert!(simulation.trail(head.handle).all(|p| p == Vec2d { x: head.x, y: head.y }));
+  }
+
+  #[test]
+  fn test_sleep_is_a_repeating_stage() {
+    // 旧版と同じく、10tick毎(age 10, 20, ...)から4tickの間止まる
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_way_num: Some(1),
+      shot_behavior: Some(vec![5]),
+      sleep_interval: Some(10.),
+      sleep_timeout: Some(4.),
+      ..options()
+    });
+    for _ in 0..59 {
+      simulation.step();
+    }
+    let mut ys = vec![];
+    for _ in 0..20 {
+      simulation.step();
+      ys.push(simulation.disks().next().unwrap().y - 400.);
+    }
+    assert_eq!(ys, vec![
+      1., 2., 3., 4., 5., 6., 7., 8., 9., 9.,
+      9., 9., 9., 10., 11., 12., 13., 14., 15., 15.,
+    ]);
+  }
+
+  #[test]
+  fn test_split_after_ticks_and_nested() {
+    // 真下に撃った弾が10tick後に4WAYに分裂、子弾はさらに5tick後に2WAYに分裂
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_way_num: Some(1),
+      shot_behavior: Some(vec![10]),
+      split_after: Some(10),
+      split: Some(Box::new(SettingOptions {
+        shot_type: Some(1),
+        shot_way_num: Some(4),
+        shot_speed: Some(2.),
+        disk_color: Some(5),
+        shot_behavior: Some(vec![10]),
+        split_after: Some(5),
+        split: Some(Box::new(SettingOptions {
+          shot_type: Some(1),
+          shot_way_num: Some(2),
+          ..SettingOptions::default()
+        })),
+        ..SettingOptions::default()
+      })),
+      ..options()
+    });
+    // 60frで発射(発射したstepでage 1) -> 69fr目にage 10で分裂
+    for _ in 0..69 {
+      simulation.step();
+    }
+    let children = simulation.disks().collect::<Vec<_>>();
+    assert_eq!(children.len(), 4);
+    assert!(children.iter().all(|d| d.disk_color == DiskColor::Blue && d.speed == 2.));
+    assert!(children.iter().all(|d| d.x == 400. && d.y == 409.));
+
+    for _ in 0..5 {
+      simulation.step();
+    }
+    assert_eq!(simulation.active_disk_count(), 8);
+    assert!(simulation.disks().all(|d| d.disk_color == DiskColor::Orange));
+  }
+
+  #[test]
+  fn test_split_on_wall() {
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_way_num: Some(1),
+      shot_speed: Some(10.),
+      x_coordinate: Some(100.), // 自機に当たらない位置
+      shot_behavior: Some(vec![10]),
+      split_on_wall: Some(true),
+      split: Some(Box::new(SettingOptions {
+        shot_type: Some(1),
+        shot_way_num: Some(3),
+        ..SettingOptions::default()
+      })),
+      ..options()
+    });
+    // 60frで発射 -> age 40(99fr目)でy = 800となり画面下端に接触
+    for _ in 0..98 {
+      simulation.step();
+    }
+    assert_eq!(simulation.active_disk_count(), 1);
+    simulation.step();
+    let disks = simulation.disks().collect::<Vec<_>>();
+    assert_eq!(disks.len(), 3);
+    assert!(disks.iter().all(|d| d.x == 100. && d.y == 800.));
+  }
+
+  #[test]
+  fn test_emitter_follows_parent_and_is_bounded() {
+    // 右に飛ぶ親弾が10tick毎に最大3回、真下に子弾を撃つ
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_way_num: Some(1),
+      shot_speed: Some(2.),
+      x_coordinate: Some(100.),
+      degree_change_by: Some(std::f64::consts::FRAC_PI_2),
+      shot_behavior: Some(vec![11]),
+      emit_interval: Some(10),
+      emit_max_shots: Some(3),
+      emit: Some(Box::new(SettingOptions {
+        shot_type: Some(1),
+        shot_way_num: Some(1),
+        shot_speed: Some(0.),
+        disk_color: Some(5),
+        ..SettingOptions::default()
+      })),
+      ..options()
+    });
+    for _ in 0..110 {
+      simulation.step();
+    }
+    let mut xs = simulation.disks()
+      .filter(|d| d.disk_color == DiskColor::Blue)
+      .map(|d| d.x)
+      .collect::<Vec<_>>();
+    xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    // 発射位置は親の移動に追従する(age 10/20/30)
+    assert_eq!(xs, vec![120., 140., 160.]);
+  }
+
+  #[test]
+  fn test_laser_warns_then_hits_player() {
+    // 画面中央から自機(真下)に向けて照射
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_type: Some(3),
+      shot_way_num: Some(1),
+      laser_warning: Some(10),
+      laser_duration: Some(20),
+      ..options()
+    });
+    for _ in 0..60 {
+      simulation.step();
+    }
+    assert_eq!(simulation.lasers().len(), 1);
+    assert_eq!(simulation.active_disk_count(), 0);
+    assert_eq!(simulation.lasers()[0].phase(), LaserPhase::Warning);
+
+    // 予告線の間は当たらない
+    for _ in 0..8 {
+      simulation.step();
+    }
+    assert_eq!(simulation.hit_count(), 0);
+    simulation.step();
+    assert_eq!(simulation.lasers()[0].phase(), LaserPhase::Active);
+    assert_eq!(simulation.hit_count(), 1);
+
+    // 照射中に当たり続けても被弾は1回
+    for _ in 0..20 {
+      simulation.step();
+    }
+    assert_eq!(simulation.hit_count(), 1);
+    assert!(simulation.lasers().is_empty());
+  }
+
+  #[test]
+  fn test_curvy_laser_body_follows_head() {
+    // 右に撃った曲がるレーザーを重力で下に曲げる
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_way_num: Some(1),
+      shot_speed: Some(4.),
+      x_coordinate: Some(100.),
+      y_coordinate: Some(100.),
+      degree_change_by: Some(std::f64::consts::FRAC_PI_2),
+      shot_behavior: Some(vec![12, 6]),
+      curvy_length: Some(10),
+      gravity_change_per: Some(5),
+      ..options()
+    });
+    for _ in 0..80 {
+      simulation.step();
+    }
+    let head = simulation.disks().next().unwrap();
+    assert!(head.has_trail);
+    let trail = simulation.trail(head.handle).collect::<Vec<_>>();
+    assert_eq!(trail.len(), 10);
+    assert_eq!(trail.last(), Some(&Vec2d { x: head.x, y: head.y }));
+    // 胴体は先頭の通った道を辿る(後ろほど上にある)
+    assert!(trail.windows(2).all(|w| w[0].x < w[1].x && w[0].y < w[1].y));
+  }
+
+  #[test]
+  fn test_curvy_laser_hits_with_body() {
+    // 自機の左から右へ水平に撃ち、先頭が自機を通り過ぎた後に自機を胴体に置く
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_way_num: Some(1),
+      shot_speed: Some(5.),
+      x_coordinate: Some(0.),
+      y_coordinate: Some(300.),
+      degree_change_by: Some(std::f64::consts::FRAC_PI_2),
+      shot_behavior: Some(vec![12]),
+      curvy_length: Some(40),
+      ..options()
+    });
+    for _ in 0..90 {
+      simulation.step();
+    }
+    let head = simulation.disks().next().unwrap();
+    assert_eq!((head.x, head.y), (155., 300.));
+    assert_eq!(simulation.hit_count(), 0);
+
+    simulation.player.x = 100.;
+    simulation.player.y = 301.;
+    simulation.step();
+    assert_eq!(simulation.hit_count(), 1);
+    // 被弾位置は自機ではなく胴体上の点
+    assert_eq!(simulation.player_events(), &[PlayerEvent::Hit { x: 100., y: 300. }]);
+    // 当たっても消えず、被弾は1回のみ
+    simulation.step();
+    assert_eq!(simulation.hit_count(), 1);
+    assert_eq!(simulation.active_disk_count(), 1);
+  }
+
+  #[test]
+  fn test_disks_leave_screen() {
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_speed: Some(100.),
+      ..options()
+    });
+    for _ in 0..60 {
+      simulation.step();
+    }
+    assert_eq!(simulation.active_disk_count(), 8);
+    for _ in 0..10 {
+      simulation.step();
+    }
+    assert_eq!(simulation.active_disk_count(), 0);
+  }
+
+  fn run_random(seed: u32, thread_seed: Option<u32>, frames: u32) -> Vec<(f64, f64)> {
+    let mut simulation = Simulation::new(&SettingOptions {
+      shot_type: Some(0),
+      seed: Some(seed),
+      thread_seed,
+      ..options()
+    });
+    for _ in 0..frames {
+      simulation.step();
+    }
+    simulation.disks().map(|d| (d.x, d.y)).collect()
+  }
+
+  #[test]
+  fn test_same_seed_reproduces_random_shot() {
+    let a = run_random(1234, None, 130);
+    let b = run_random(1234, None, 130);
+    assert_eq!(a.len(), 16);
+    assert_eq!(a, b);
+    assert_ne!(a, run_random(4321, None, 130));
+  }
+
+  #[test]
+  fn test_thread_seed_overrides_screen_seed() {
+    let a = run_random(1, Some(99), 90);
+    let b = run_random(2, Some(99), 90);
+    assert_eq!(a, b);
+  }
+}
@@ -0,0 +1,135 @@
+use super::shot::{ HomingTarget, ShotBehavior };
+
+/**
+ * 弾の挙動の1段階
+ * startのageに達したら開始し、次の段階のstartまで続く
+ */
+#[derive(Debug, Clone)]
+pub struct Stage {
+  pub start: u32, // この段階に入るage
+  pub behavior: Vec<ShotBehavior>, // 段階中に毎tick適用する挙動(グループの挙動に追加)
+  pub stop: bool, // 段階中は移動しない
+  pub speed: Option<f64>, // 段階の開始時に速度を変える
+  pub aim: Option<HomingTarget>, // 段階の開始時に目標へ向き直す
+}
+
+impl Stage {
+  pub fn new(start: u32) -> Self {
+    Stage {
+      start,
+      behavior: vec![],
+      stop: false,
+      speed: None,
+      aim: None,
+    }
+  }
+}
+
+/**
+ * Diskのageをキーにした挙動の段階切り替え
+ * periodが0より大きい場合はage % periodで評価し、段階を繰り返す
+ * delayが0より大きい場合はageがdelayに達するまで段階なしで、以降はage - delayで評価する
+ */
+#[derive(Debug, Clone, Default)]
+pub struct Timeline {
+  stages: Vec<Stage>, // startの昇順
+  period: u32,
+  delay: u32,
+}
+
+impl Timeline {
+  pub fn new(mut stages: Vec<Stage>, period: u32) -> Self {
+    stages.sort_by_key(|stage| stage.start);
+    Timeline { stages, period, delay: 0 }
+  }
+
+  pub fn with_delay(mut self, delay: u32) -> Self {
+    self.delay = delay;
+    self
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.stages.is_empty()
+  }
+
+  pub fn stages(&self) -> &[Stage] {
+    &self.stages
+  }
+
+  pub fn period(&self) -> u32 {
+    self.period
+  }
+
+  pub fn delay(&self) -> u32 {
+    self.delay
+  }
+
+  /**
+   * ageでの段階と、そのageで段階に入ったかどうか
+   * 最初の段階のstartより前は段階なし
+   * 発射時(age 0)は更新されないので、start 0の段階の開始時処理は繰り返し時のみ行われる
+   */
+  pub fn stage_at(&self, age: u32) -> Option<(&Stage, bool)> {
+    let age = age.checked_sub(self.delay)?;
+    let age = if self.period > 0 { age % self.period } else { age };
+    self.stages
+      .iter()
+      .rev()
+      .find(|stage| stage.start <= age)
+      .map(|stage| (stage, stage.start == age))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn starts(timeline: &Timeline, ages: std::ops::Range<u32>) -> Vec<Option<(u32, bool)>> {
+    ages
+      .map(|age| timeline.stage_at(age).map(|(stage, entered)| (stage.start, entered)))
+      .collect()
+  }
+
+  #[test]
+  fn test_stage_at() {
+    // 順不同で渡しても開始ageの順になる
+    let timeline = Timeline::new(vec![Stage::new(5), Stage::new(2)], 0);
+    assert_eq!(starts(&timeline, 0..7), vec![
+      None,
+      None,
+      Some((2, true)),
+      Some((2, false)),
+      Some((2, false)),
+      Some((5, true)),
+      Some((5, false)),
+    ]);
+  }
+
+  #[test]
+  fn test_stage_repeats_with_period() {
+    let timeline = Timeline::new(vec![Stage::new(0), Stage::new(2)], 3);
+    assert_eq!(starts(&timeline, 1..7), vec![
+      Some((0, false)),
+      Some((2, true)),
+      Some((0, true)),
+      Some((0, false)),
+      Some((2, true)),
+      Some((0, true)),
+    ]);
+    assert!(Timeline::default().stage_at(10).is_none());
+  }
+
+  #[test]
+  fn test_stage_waits_for_delay() {
+    let timeline = Timeline::new(vec![Stage::new(0), Stage::new(2)], 3).with_delay(3);
+    assert_eq!(starts(&timeline, 1..8), vec![
+      None,
+      None,
+      Some((0, true)),
+      Some((0, false)),
+      Some((2, true)),
+      Some((0, true)),
+      Some((0, false)),
+    ]);
+  }
+}
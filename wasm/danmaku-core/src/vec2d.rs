@@ -77,10 +77,16 @@ impl Sub for Vec2d {
 mod test {
   use super::*;
 
+  #[test]
+  fn vec2d_new() {
+    let v = Vec2d::new(0., 2.);
+    assert_eq!(v, Vec2d { x: 0., y: 2. });
+  }
+
   // #[test]
   // fn vec2d_distance() {
   //   let criteria = Vec2d::new(5., 5.);
   //   let v = criteria.distance_from(Vec2d::new(10., 10.));
   //   assert_eq!(v, (50.0f64).sqrt());
   // }
-}
\ No newline at end of file
+}
@@ -1,6 +1,6 @@
 use wasm_bindgen::JsCast;
 use web_sys::{
-    Document, HtmlCanvasElement, HtmlImageElement, WebGlProgram, WebGlRenderingContext, WebGlShader, Window,
+    Document, HtmlCanvasElement, HtmlImageElement, Window,
     CanvasRenderingContext2d
 };
 
@@ -1,27 +1,10 @@
 mod dom_utils;
 mod utils;
-mod vec2d;
-mod rectanble;
-
-mod disk;
-mod shot;
-mod setting;
-mod schedule;
-mod event;
-mod event_thread;
-
-use setting::Setting;
-use shot::ShotBehavior;
-use vec2d::Vec2d;
-// use rand::Rng;
-// use serde::{Deserialize, Serialize};
+
+use danmaku_core::{ Simulation, SettingOptions, ThreadId, DiskType, DiskColor, DiskView, LaserPhase, PlayerInput };
 use wasm_bindgen::prelude::*;
 use web_sys::console::log_1;
-use web_sys::{WebGlBuffer, WebGlRenderingContext, WebGlUniformLocation, HtmlImageElement, CanvasRenderingContext2d};
-// use vec2d::{Vec2d};
-use disk::{ Disk, DiskType, DiskColor };
-use schedule::{ Schedule };
-use event_thread::{ EventThread };
+use web_sys::{ HtmlImageElement, CanvasRenderingContext2d };
 
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
 // allocator.
@@ -36,7 +19,7 @@ macro_rules! log {
     }
 }
 
-pub fn log(s: &String) {
+pub fn log(s: &str) {
     log_1(&JsValue::from(s));
 }
 
@@ -45,17 +28,12 @@ pub fn output_log(s: &str) {
     log(&format!("Hello {}", s));
 }
 
-const FRAMES_PER_SEC: u32 = 60;
-const MILLI_SECONDS: u32 = 1_000;
-const DISK_NUM: u32 = 4_096;
-
 /**
- * 定数をもとにインターバル(ms)をフレーム数に変換する
- * ex) 500ms -> 30fr
- *     200ms -> 12fr
+ * Jsからの入力値をSettingOptionsに変換する
+ * 変換できない入力値はJsの例外にする
  */
-pub fn convert_interval_to_frame(interval: u32) -> f64 {
-    (FRAMES_PER_SEC as f64) * ((interval as f64) / (MILLI_SECONDS as f64))
+fn parse_options(option_input: JsValue) -> Result<SettingOptions, JsValue> {
+    serde_wasm_bindgen::from_value(option_input).map_err(JsValue::from)
 }
 
 #[derive(Debug)]
@@ -68,22 +46,19 @@ pub struct Screen {
 
     sprite_sheet: HtmlImageElement,
 
-    disks: Vec<Option<Disk>>,
-    schedule: Schedule,
+    simulation: Simulation,
 
     fps_counter: u32,
     fps_time: f64,
     last_fps: u32,
 }
 
-type ThreadId = u32;
-
 #[wasm_bindgen]
 impl Screen {
     /**
      * アニメーション実行
      */
-    pub fn do_frame(&mut self, time: f64) -> () {
+    pub fn do_frame(&mut self, time: f64) {
         self.on_animation_frame(time);
         self.draw();
     }
@@ -92,170 +67,85 @@ impl Screen {
      * ThreadID一覧
      */
     pub fn get_thread_ids(&self) -> Vec<ThreadId> {
-        self.schedule.threads
-            .iter()
-            .map(|thread| thread.id)
-            .collect()
+        self.simulation.thread_ids()
     }
-    
+
     /**
-     * settings作成/更新
+     * 使用中の乱数シード
      */
-    pub fn upsert_thread_setting(&mut self, thread_id: Option<u32>, option_input: JsValue) -> Option<ThreadId> {
-        log!("options {:?}", option_input); 
-        let options: setting::SettingOptions = option_input.into_serde().unwrap();
-        let setting = setting::Setting::new(&options);
-        let iteration_ms = setting.iteration_ms;
-        let thread = match thread_id {
-            Some(id) => {
-                self.schedule
-                    .threads
-                    .iter()
-                    .find(|thread| thread.id == id)
-                    .map(|thread| {
-                        let mut cloned = thread.clone();
-                        cloned.update_setting(setting);
-                        cloned 
-                    })
-            },
-            None => {
-                let thread_id = self.schedule.generate_id();
-                Some(
-                    EventThread::new(
-                        thread_id,
-                        setting,
-                    )
-                )
-            },
-        }?;
-        let thread_id = thread.id;
-        self.schedule.set_end_at(iteration_ms);
-        self.schedule.subscribe_thread(thread);
-        self.schedule.refresh_events();
-        Some(thread_id)
+    pub fn get_seed(&self) -> u32 {
+        self.simulation.seed()
     }
-}
 
-impl Screen {
     /**
-     * 各アニメーションフレームごとの処理
+     * 自機の入力状態を更新
      */
-    fn on_animation_frame(&mut self, time: f64) -> () {
-        self.schedule.iterate();
+    pub fn set_input(&mut self, up: bool, down: bool, left: bool, right: bool, focus: bool) {
+        self.simulation.set_player_input(PlayerInput { up, down, left, right, focus });
+    }
 
-        /* スケジュールされたイベントの走査 */
-        let mut disks: &mut Vec<Option<Disk>> = self.disks.as_mut();
-        self.schedule.walkthrough_events(&mut disks);
+    /**
+     * 累計被弾数
+     */
+    pub fn get_hit_count(&self) -> u32 {
+        self.simulation.hit_count()
+    }
 
-        /* Diskのステータスで座標更新 */
-        self.update_disks();
+    /**
+     * 累計グレイズ数
+     */
+    pub fn get_graze_count(&self) -> u32 {
+        self.simulation.graze_count()
+    }
 
-        /* fps更新 */
-        self.calc_fps(time);
+    /**
+     * Diskプール満杯で破棄された累計弾数
+     */
+    pub fn get_dropped_count(&self) -> u32 {
+        self.simulation.dropped_disk_count()
     }
 
     /**
-     * 反射時処理
+     * Diskプール満杯時に空きを作るため消された累計弾数
      */
-    fn on_reflect(disk: &mut Option<Disk>, width: f64, height: f64) {
-        if let Some(v) = disk {
-            let size = v.disk_size;
-            let should_reflect = v.reflect_count.unwrap_or(0) > 0;
-            let reflect_behavior = v.behavior
-                .iter()
-                .find(|&sb| match sb {
-                    ShotBehavior::Reflect(_) => true,
-                    _ => false,
-                });
-            match (should_reflect, reflect_behavior) {
-                (true, Some(ShotBehavior::Reflect(Some(_)))) => {
-                    // X軸方向
-                    if v.x - size < 0. || v.x + size > width {
-                        v.x -= v.vec2d.x;
-                        v.vec2d.x = -v.vec2d.x;
-                        v.reflect_count = v.reflect_count.map(|num| num - 1);
-                    }
-                    // Y軸方向
-                    if v.y - size < 0. || v.y + size > height {
-                        v.y -= v.vec2d.y;
-                        v.vec2d.y = -v.vec2d.y;
-                        v.reflect_count = v.reflect_count.map(|num| num - 1);
-                    }
-                }
-                _ => {
-                    // 通常弾の場合
-                    if v.x + size < 0. || v.x - size > width || v.y + size < 0. || v.y - size > height {
-                        disk.take();
-                    }
-                }
-            }
-        }
+    pub fn get_evicted_count(&self) -> u32 {
+        self.simulation.evicted_disk_count()
+    }
+
+    /**
+     * 直近フレームで発生した自機イベント一覧
+     * ex) [{ kind: "Graze", x: 412, y: 670 }, { kind: "Hit", x: 400, y: 680 }]
+     */
+    pub fn get_player_events(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(self.simulation.player_events()).unwrap()
+    }
+
+    /**
+     * settings作成/更新
+     */
+    pub fn upsert_thread_setting(&mut self, thread_id: Option<u32>, option_input: JsValue) -> Result<Option<ThreadId>, JsValue> {
+        log!("options {:?}", option_input);
+        let options = parse_options(option_input)?;
+        Ok(self.simulation.upsert_thread_setting(thread_id, &options))
     }
+}
 
+impl Screen {
     /**
-     * 登録しているDiskのステータスに従って座標を更新
+     * 各アニメーションフレームごとの処理
      */
-    fn update_disks(&mut self) {
-        let width = self.width;
-        let height  = self.height;
-        self.disks
-            .iter_mut()
-            .for_each(|disk| {
-                if let Some(v) = disk {
-                    v.gain_age(1);
-
-                    // スリープ制御
-                    // TODO: ShotBehavior用の解析関数作る
-                    v.behavior
-                        .clone()
-                        .iter()
-                        .for_each(|&sb| {
-                            match sb {
-                                // スリープ
-                                ShotBehavior::Sleep(interval, timeout) => {
-                                    v.sleep_time += {
-                                        if interval == 0 {
-                                            0
-                                        } else if v.age % (interval as u32) == 0 {
-                                            timeout
-                                        } else if v.sleep_time > 0 {
-                                            -1
-                                        } else {
-                                            0
-                                        }
-                                    }
-                                },
-                                ShotBehavior::SpeedDown(_, per) => {
-                                    v.speed -= v.speed * per; 
-                                    v.vec2d = Vec2d::new(v.angle, v.speed);
-                                },
-                                ShotBehavior::SpeedUp(_, per) => {
-                                    v.speed += v.speed * per; 
-                                    v.vec2d = Vec2d::new(v.angle, v.speed);
-                                },
-                                // 重力減衰/加速
-                                ShotBehavior::Gravity(direction, by) => {
-                                    let angle = std::f64::consts::PI * (90. * direction as f64) / 180.;
-                                    let vec2d = Vec2d::new(angle, v.speed * by);
-                                    v.vec2d = v.vec2d + vec2d;
-                                }
-                                _ => (),
-                            }
-                        });
-                    
-                    if v.sleep_time > 0 { return }
-                    v.x += v.vec2d.x;
-                    v.y += v.vec2d.y;
-                }
-
-                Screen::on_reflect(disk, width, height);
-            });
+    fn on_animation_frame(&mut self, time: f64) {
+        /* 経過時間分だけシミュレーションを進める(リフレッシュレートに依存しない) */
+        self.simulation.advance(time);
+
+        /* fps更新 */
+        self.calc_fps(time);
     }
 
     fn resolve_sprite_src(&self, disk_type: &DiskType, disk_color: &DiskColor) -> (f64, f64, f64, f64) {
         let casted_disk_color = (*disk_color as usize) as f64;
         match disk_type {
-            DiskType::Oval => (0. + (casted_disk_color * 10.), 0., 10., 10.),
+            DiskType::Oval => (casted_disk_color * 10., 0., 10., 10.),
             DiskType::Dot => (2. + (casted_disk_color * 24.), 12., 18., 18.),
             DiskType::Circle => (2. + (casted_disk_color * 24.), 37., 18., 18.),
             DiskType::Orb => (2. + (casted_disk_color * 30.), 61., 24., 24.),
@@ -263,92 +153,161 @@ impl Screen {
         }
     }
 
+    /**
+     * レーザーの描画色(弾のスプライトの色に合わせる)
+     */
+    fn resolve_laser_color(&self, disk_color: &DiskColor) -> &'static str {
+        match disk_color {
+            DiskColor::Red => "rgb(255, 60, 60)",
+            DiskColor::Orange => "rgb(255, 150, 40)",
+            DiskColor::Yellow => "rgb(255, 230, 50)",
+            DiskColor::Green => "rgb(60, 220, 80)",
+            DiskColor::LightBlue => "rgb(90, 220, 255)",
+            DiskColor::Blue => "rgb(60, 110, 255)",
+            DiskColor::DeepBlue => "rgb(40, 40, 180)",
+            DiskColor::Purple => "rgb(170, 70, 240)",
+            DiskColor::Pink => "rgb(255, 120, 200)",
+        }
+    }
+
     /**
      * レンダリング処理
      */
     fn draw(&self) {
         self.context.save();
         let bg_color = if self.theme == 0 { "rgb(200, 200, 200, 1.0)" } else { "rgb(80, 80, 80, 1.0)" };
-        self.context.set_fill_style(&JsValue::from(bg_color));
-        self.context.fill_rect(0., 0., self.width as f64, self.height as f64); 
-
-        for disk in self.disks.iter() {
-            match disk {
-                Some(d) => {
-                    let sprite = self.resolve_sprite_src(&d.disk_type, &d.disk_color);
-                    self.context.draw_image_with_html_image_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
-                        &self.sprite_sheet,
-                        sprite.0,
-                        sprite.1,
-                        sprite.2,
-                        sprite.3,
-                        (d.x as f64) - (d.disk_size / 2.),
-                        (d.y as f64) - (d.disk_size / 2.),
-                        d.disk_size,
-                        d.disk_size, 
-                    ).unwrap();
-                },
-                _ => {
-                    continue;
-                }
+        self.context.set_fill_style_str(bg_color);
+        self.context.fill_rect(0., 0., self.width, self.height);
+
+        for d in self.simulation.disks() {
+            if d.has_trail {
+                self.draw_curvy_laser(&d);
+                continue;
             }
+            let sprite = self.resolve_sprite_src(&d.disk_type, &d.disk_color);
+            self.context.draw_image_with_html_image_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                &self.sprite_sheet,
+                sprite.0,
+                sprite.1,
+                sprite.2,
+                sprite.3,
+                d.x - (d.disk_size / 2.),
+                d.y - (d.disk_size / 2.),
+                d.disk_size,
+                d.disk_size,
+            ).unwrap();
         }
 
-        // TODO: モニタ部分をカスタムしやすいように別関数&座標計算入れるようにしたい
+        self.draw_lasers();
+        self.draw_player();
+
         // アクティブ段数
-        let active_disk_count = self.disks
-            .iter()
-            .filter_map(|disk| {
-                match disk {
-                    Some(_) => Some(1),
-                    _ => None,
-                }
-            })
-            .count();
-
-        self.context.set_fill_style(&JsValue::from("rgba(0, 0, 0, 0.5)"));
-        self.context.fill_rect(10., 730., 180., 45.);
-        self.context.set_fill_style(&JsValue::from("rgb(255, 255, 255)"));
-        self.context.set_stroke_style(&JsValue::from("rgb(255, 255, 255)"));
-        self.context.set_font("16px sans-serif");
-        self.context.fill_text(&format!("FPS: {}", self.last_fps), 15., 750.);
-        self.context.fill_text(&format!("アクティブ弾数: {}", active_disk_count), 15., 770.);
+        let active_disk_count = self.simulation.active_disk_count();
+        self.draw_monitor(&[
+            format!("FPS: {}", self.last_fps),
+            format!("アクティブ弾数: {}/{}", active_disk_count, self.simulation.disk_capacity()),
+            format!("破棄弾数: {}", self.simulation.dropped_disk_count() + self.simulation.evicted_disk_count()),
+            format!("被弾数: {}", self.simulation.hit_count()),
+            format!("グレイズ数: {}", self.simulation.graze_count()),
+        ]);
 
         self.context.restore();
     }
 
+    /**
+     * レーザー描画(予告線は細く半透明、照射中は太さ分の線)
+     */
+    fn draw_lasers(&self) {
+        self.context.set_line_cap("round");
+        for laser in self.simulation.lasers() {
+            let (width, alpha) = match laser.phase() {
+                LaserPhase::Warning => (1., 0.5),
+                _ => (laser.spec.width, 0.9),
+            };
+            let (ex, ey) = laser.end();
+            self.context.set_global_alpha(alpha);
+            self.context.set_stroke_style_str(self.resolve_laser_color(&laser.spec.color));
+            self.context.set_line_width(width);
+            self.context.begin_path();
+            self.context.move_to(laser.x, laser.y);
+            self.context.line_to(ex, ey);
+            self.context.stroke();
+        }
+        self.context.set_global_alpha(1.);
+    }
+
+    /**
+     * 曲がるレーザー描画(軌跡を弾の大きさの太さで繋いだ帯)
+     */
+    fn draw_curvy_laser(&self, d: &DiskView) {
+        self.context.set_line_cap("round");
+        self.context.set_line_join("round");
+        self.context.set_stroke_style_str(self.resolve_laser_color(&d.disk_color));
+        self.context.set_line_width(d.disk_size);
+        self.context.begin_path();
+        for (i, p) in self.simulation.trail(d.handle).enumerate() {
+            if i == 0 {
+                self.context.move_to(p.x, p.y);
+            } else {
+                self.context.line_to(p.x, p.y);
+            }
+        }
+        self.context.stroke();
+    }
+
+    /**
+     * 自機描画(外側が機体、中心の赤丸が当たり判定)
+     */
+    fn draw_player(&self) {
+        let player = self.simulation.player();
+        self.context.set_fill_style_str("rgba(255, 255, 255, 0.8)");
+        self.context.begin_path();
+        self.context.arc(player.x, player.y, player.hit_radius * 3., 0., std::f64::consts::PI * 2.).unwrap();
+        self.context.fill();
+        self.context.set_fill_style_str("rgb(255, 0, 0)");
+        self.context.begin_path();
+        self.context.arc(player.x, player.y, player.hit_radius, 0., std::f64::consts::PI * 2.).unwrap();
+        self.context.fill();
+    }
+
+    /**
+     * 画面左下にモニタ情報を描画
+     */
+    fn draw_monitor(&self, lines: &[String]) {
+        let line_height = 20.;
+        let box_height = line_height * lines.len() as f64 + 5.;
+        let top = self.height - 25. - box_height;
+        self.context.set_fill_style_str("rgba(0, 0, 0, 0.5)");
+        self.context.fill_rect(10., top, 180., box_height);
+        self.context.set_fill_style_str("rgb(255, 255, 255)");
+        self.context.set_stroke_style_str("rgb(255, 255, 255)");
+        self.context.set_font("16px sans-serif");
+        for (i, line) in lines.iter().enumerate() {
+            self.context.fill_text(line, 15., top + line_height * (i + 1) as f64).unwrap();
+        }
+    }
+
     /**
      * FPS計算
      */
     fn calc_fps(&mut self, time: f64) {
         if self.fps_time + 1000. < time {
-            self.last_fps = self.fps_counter;  
-            self.fps_counter = 0;   
+            self.last_fps = self.fps_counter;
+            self.fps_counter = 0;
             self.fps_time = time;
         }
         self.fps_counter += 1;
     }
 }
 
-/**
- * ディスクのベクタを初期化する
- */
-fn init_disks(disk_num: u32) -> Vec<Option<Disk>> {
-    let mut disks_buffer: Vec<Option<Disk>> = Vec::with_capacity(disk_num as usize);
-    for _ in 0..disk_num {
-        disks_buffer.push(None);
-    }
-    disks_buffer
-}
-
 #[wasm_bindgen]
-pub fn init_screen(option_input: JsValue) -> Screen {
-    log!("options {:?}", option_input); 
-    let options: setting::SettingOptions = option_input.into_serde().unwrap();
-    let setting = setting::Setting::new(&options);
+pub fn init_screen(option_input: JsValue) -> Result<Screen, JsValue> {
+    utils::set_panic_hook();
+    log!("options {:?}", option_input);
+    let options = parse_options(option_input)?;
 
     // Screen情報
-    let canvas_id = options.canvas_id;
+    let canvas_id = options.canvas_id.clone();
     let width = options.width;
     let height = options.height;
     let theme = options.theme;
@@ -356,31 +315,19 @@ pub fn init_screen(option_input: JsValue) -> Screen {
     // TODO: DOM操作系はResultsを返すようにしてエラーをキャッチしたい
     let context = dom_utils::get_context2d_by_id(canvas_id.as_str(), width, height).unwrap();
 
-    // Disks初期化
-    let disks = init_disks(DISK_NUM);
-
-    // Scheduleの初期化と最初のEventThreadを登録
-    let mut schedule = Schedule::new();
-    let thread_id = schedule.generate_id();
-    let thread = EventThread::new(
-        thread_id,
-        setting,
-    );
-    schedule.set_end_at(options.iteration_ms);
-    schedule.subscribe_thread(thread);
-    schedule.refresh_events();
+    // シミュレーション初期化
+    let simulation = Simulation::new(&options);
 
     // 弾のスプライト取得
     let img = dom_utils::image("img-src").unwrap();
 
-    Screen {
+    Ok(Screen {
         width,
         height,
         theme,
         context,
 
-        disks,
-        schedule,
+        simulation,
 
         sprite_sheet: img,
 
@@ -388,5 +335,5 @@ pub fn init_screen(option_input: JsValue) -> Screen {
         fps_counter: 0,
         fps_time: 0.,
         last_fps: 0,
-    }
+    })
 }